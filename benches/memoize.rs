@@ -0,0 +1,50 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use nessie_parse::{ParseResult, Parser, Session, State};
+
+type P<'a, T> = Parser<'a, T, ()>;
+
+/// `x`, followed by `depth` signs. Every level tries three alternatives that
+/// all start with the level below it, so without memoization the `x` is
+/// parsed `3^depth` times.
+fn signs<'a>(depth: usize, memoize: bool) -> P<'a, usize> {
+    let mut parser = P::char_eq('x').map(|_| 0);
+    for level in 1..=depth {
+        if memoize {
+            parser = parser.memoize();
+        }
+        let plus = parser
+            .clone()
+            .and_then(|n| P::char_eq('+').map(move |_| n + 1));
+        let minus = parser
+            .clone()
+            .and_then(|n| P::char_eq('-').map(move |_| n + 1));
+        // A short name, since the default one has the names of all of the
+        // alternatives, which is exponential too.
+        parser = P::one_of([plus, minus, parser])
+            .map_fail(|()| ())
+            .with_name(format!("signs{level}"));
+    }
+    parser
+}
+
+fn backtracking(c: &mut Criterion) {
+    let mut group = c.benchmark_group("backtracking");
+    for depth in [4, 8, 12] {
+        let text = "x".to_string() + &"+-".repeat(depth / 2);
+        for memoize in [false, true] {
+            let id = if memoize { "memoize" } else { "plain" };
+            group.bench_with_input(BenchmarkId::new(id, depth), &text, |b, text| {
+                b.iter(|| {
+                    let session = Session::new();
+                    let parser = signs(depth, memoize);
+                    let result = parser.parse(State::new(text).with_session(&session));
+                    assert!(matches!(result, ParseResult::Ok(n, _) if n == depth));
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, backtracking);
+criterion_main!(benches);
@@ -0,0 +1,173 @@
+use crate::parser::{ParseResult, Parser};
+use crate::state::State;
+
+use std::fmt;
+
+/// The failure of a binary primitive. It is reported at the byte offset
+/// where the primitive started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryFailure {
+    /// The input ended, and `needed` more bytes were needed.
+    Eof { needed: usize },
+    /// The bytes were not the expected tag.
+    BadTag,
+    /// A number did not fit in its type.
+    Overflow,
+    /// A length-prefixed block was not all used, and `left` bytes were left.
+    Unused { left: usize },
+}
+
+impl fmt::Display for BinaryFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BinaryFailure::Eof { needed: 1 } => write!(f, "expected 1 more byte"),
+            BinaryFailure::Eof { needed } => write!(f, "expected {needed} more bytes"),
+            BinaryFailure::BadTag => write!(f, "bad tag"),
+            BinaryFailure::Overflow => write!(f, "number is too big"),
+            BinaryFailure::Unused { left } => write!(f, "{left} bytes left in the block"),
+        }
+    }
+}
+
+impl<'a, E: 'a> Parser<'a, &'a [u8], E, BinaryFailure, [u8]> {
+    /// Parses the next `n` bytes.
+    pub fn take(n: usize) -> Self {
+        Parser::from_fn(move |state: State<'a, [u8]>| {
+            let rest = state.rest();
+            match rest.get(..n) {
+                Some(bytes) => ParseResult::Ok(bytes, state.skip(n)),
+                None => {
+                    let needed = n - rest.len();
+                    state.out_of_input(needed, BinaryFailure::Eof { needed })
+                }
+            }
+        })
+        .with_name(format!("take({n})"))
+    }
+}
+
+impl<'a, E: 'a> Parser<'a, (), E, BinaryFailure, [u8]> {
+    /// Parses exactly the bytes `tag`, like the magic number at the start of
+    /// a file format.
+    pub fn tag(tag: &'a [u8]) -> Self {
+        Parser::from_fn(move |state: State<'a, [u8]>| {
+            let rest = state.rest();
+            let len = tag.len().min(rest.len());
+            if rest[..len] != tag[..len] {
+                ParseResult::Fail(BinaryFailure::BadTag, state.pos)
+            } else if len < tag.len() {
+                let needed = tag.len() - len;
+                state.out_of_input(needed, BinaryFailure::Eof { needed })
+            } else {
+                ParseResult::Ok((), state.skip(len))
+            }
+        })
+        .with_name(format!("tag({tag:?})"))
+    }
+}
+
+/// Parses the next `N` bytes, for the fixed-size primitives.
+fn array<'a, E: 'a, const N: usize>() -> Parser<'a, [u8; N], E, BinaryFailure, [u8]> {
+    Parser::take(N).map(|bytes| bytes.try_into().unwrap())
+}
+
+impl<'a, E: 'a> Parser<'a, u8, E, BinaryFailure, [u8]> {
+    pub fn u8() -> Self {
+        array().map(u8::from_le_bytes).with_name("u8")
+    }
+}
+
+/// Implements the little and big endian parsers of a number type.
+macro_rules! endian_primitives {
+    ($($ty:ident: $le:ident, $be:ident;)*) => {$(
+        impl<'a, E: 'a> Parser<'a, $ty, E, BinaryFailure, [u8]> {
+            #[doc = concat!("Parses a little endian `", stringify!($ty), "`.")]
+            pub fn $le() -> Self {
+                array().map($ty::from_le_bytes).with_name(stringify!($le))
+            }
+
+            #[doc = concat!("Parses a big endian `", stringify!($ty), "`.")]
+            pub fn $be() -> Self {
+                array().map($ty::from_be_bytes).with_name(stringify!($be))
+            }
+        }
+    )*};
+}
+
+endian_primitives! {
+    u16: u16_le, u16_be;
+    u32: u32_le, u32_be;
+    u64: u64_le, u64_be;
+    f32: f32_le, f32_be;
+    f64: f64_le, f64_be;
+}
+
+impl<'a, E: 'a> Parser<'a, u64, E, BinaryFailure, [u8]> {
+    /// Parses an unsigned LEB128 number, where every byte holds 7 bits of the
+    /// number (the lowest first), and its high bit says if more bytes follow.
+    pub fn varint() -> Self {
+        Parser::from_fn(|state: State<'a, [u8]>| {
+            let mut value = 0u64;
+            for (i, &byte) in state.rest().iter().enumerate() {
+                let bits = u64::from(byte & 0x7f);
+                let shift = 7 * i as u32;
+                if shift >= 64 || (bits << shift) >> shift != bits {
+                    return ParseResult::Fail(BinaryFailure::Overflow, state.pos);
+                }
+                value |= bits << shift;
+                if byte & 0x80 == 0 {
+                    return ParseResult::Ok(value, state.skip(i + 1));
+                }
+            }
+            state.out_of_input(1, BinaryFailure::Eof { needed: 1 })
+        })
+        .with_name("varint")
+    }
+}
+
+impl<'a, T: 'a, E: 'a, F: From<BinaryFailure> + 'a> Parser<'a, T, E, F, [u8]> {
+    /// Parses a length with `len`, and then a block of exactly that many
+    /// bytes with `inner`. `inner` can't read past the end of the block, and
+    /// it fails if it does not use all of it. Positions inside the block are
+    /// still from the start of the whole input.
+    pub fn length_prefixed<N>(len: Parser<'a, N, E, F, [u8]>, inner: Self) -> Self
+    where
+        N: TryInto<usize> + 'a,
+    {
+        let name = format!("length_prefixed({}, {})", len.name, inner.name);
+        Parser::from_fn(move |state: State<'a, [u8]>| {
+            let (n, start) = match len.parse(state) {
+                ParseResult::Ok(n, pos) => (n, pos),
+                ParseResult::Fail(f, pos) => return ParseResult::Fail(f, pos),
+                ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+            };
+            let end = n.try_into().ok().and_then(|n| start.offset.checked_add(n));
+            let Some(end) = end else {
+                return ParseResult::Fail(BinaryFailure::Overflow.into(), state.pos);
+            };
+            if end > state.text.len() {
+                let needed = end - state.text.len();
+                let state = state.with_pos(start);
+                return state.out_of_input(needed, BinaryFailure::Eof { needed }.into());
+            }
+            // All of the block is there, so it is not partial.
+            let block = State {
+                text: &state.text[..end],
+                partial: false,
+                ..state.with_pos(start)
+            };
+            match inner.parse(block) {
+                ParseResult::Ok(x, pos) if pos.offset == end => ParseResult::Ok(x, pos),
+                ParseResult::Ok(_, pos) => {
+                    let left = end - pos.offset;
+                    ParseResult::Fail(BinaryFailure::Unused { left }.into(), pos)
+                }
+                ParseResult::Fail(f, pos) => ParseResult::Fail(f, pos),
+                ParseResult::Err(e, pos) => ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+            }
+        })
+        .with_name(name)
+    }
+}
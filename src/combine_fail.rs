@@ -1,54 +1,54 @@
 use crate::State;
 
-pub trait CombineFail<'a, Rhs, Out> {
-    fn combine_fail(self, self_state: State<'a>, other: Rhs, other_state: State<'a>) -> Out;
+pub trait CombineFail<'a, Rhs, Out, I: ?Sized = str> {
+    fn combine_fail(self, self_state: State<'a, I>, other: Rhs, other_state: State<'a, I>) -> Out;
 }
 
-pub trait CombineManyFail<'text, Out>: Sized {
-    fn combine_many_fail(fails: Vec<(Self, State<'text>)>) -> Out;
+pub trait CombineManyFail<'text, Out, I: ?Sized = str>: Sized {
+    fn combine_many_fail(fails: Vec<(Self, State<'text, I>)>) -> Out;
 }
 
 // Let's have some useful implementations!
 
 // Implementations for combining to ().
 
-impl<F1, F2> CombineFail<'_, F2, ()> for F1 {
-    fn combine_fail(self, _: State, _: F2, _: State) {}
+impl<F1, F2, I: ?Sized> CombineFail<'_, F2, (), I> for F1 {
+    fn combine_fail(self, _: State<I>, _: F2, _: State<I>) {}
 }
 
-impl<F> CombineManyFail<'_, ()> for F {
-    fn combine_many_fail(_: Vec<(Self, State)>) {}
+impl<F, I: ?Sized> CombineManyFail<'_, (), I> for F {
+    fn combine_many_fail(_: Vec<(Self, State<I>)>) {}
 }
 
 // Now for just saving everything
 
-impl<'a, F1, F2> CombineFail<'a, F2, (F1, State<'a>, F2, State<'a>)> for F1 {
+impl<'a, F1, F2, I: ?Sized> CombineFail<'a, F2, (F1, State<'a, I>, F2, State<'a, I>), I> for F1 {
     fn combine_fail(
         self,
-        s1: State<'a>,
+        s1: State<'a, I>,
         other: F2,
-        s2: State<'a>,
-    ) -> (F1, State<'a>, F2, State<'a>) {
+        s2: State<'a, I>,
+    ) -> (F1, State<'a, I>, F2, State<'a, I>) {
         (self, s1, other, s2)
     }
 }
 
-impl<'a, F> CombineManyFail<'a, Vec<(F, State<'a>)>> for F {
-    fn combine_many_fail(fails: Vec<(Self, State)>) -> Vec<(Self, State)> {
+impl<'a, F, I: ?Sized> CombineManyFail<'a, Vec<(F, State<'a, I>)>, I> for F {
+    fn combine_many_fail(fails: Vec<(Self, State<'a, I>)>) -> Vec<(Self, State<'a, I>)> {
         fails
     }
 }
 
 // Now for saving the failure, but not the parsing state.
 
-impl<F1, F2> CombineFail<'_, F2, (F1, F2)> for F1 {
-    fn combine_fail(self, _: State, other: F2, _: State) -> (F1, F2) {
+impl<F1, F2, I: ?Sized> CombineFail<'_, F2, (F1, F2), I> for F1 {
+    fn combine_fail(self, _: State<I>, other: F2, _: State<I>) -> (F1, F2) {
         (self, other)
     }
 }
 
-impl<F> CombineManyFail<'_, Vec<F>> for F {
-    fn combine_many_fail(fails: Vec<(Self, State)>) -> Vec<Self> {
+impl<F, I: ?Sized> CombineManyFail<'_, Vec<F>, I> for F {
+    fn combine_many_fail(fails: Vec<(Self, State<I>)>) -> Vec<Self> {
         fails.into_iter().map(|(f, _)| f).collect()
     }
 }
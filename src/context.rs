@@ -0,0 +1,95 @@
+use crate::parser::{ParseResult, Parser};
+use crate::session::Session;
+use crate::state::State;
+
+use std::rc::Rc;
+
+/// Returns the session of `state`, or panics with a helpful message.
+/// Contexts live in the session, so parsers that use them can't run without
+/// one.
+fn session_of<'a, I: ?Sized>(state: &State<'a, I>, parser: &str) -> &'a Session {
+    match state.session {
+        Some(session) => session,
+        None => panic!("{parser}: the state has no session to keep the context in"),
+    }
+}
+
+fn context_of<C: 'static, I: ?Sized>(state: &State<I>, parser: &str) -> Rc<C> {
+    match session_of(state, parser).context::<C>() {
+        Some(context) => context,
+        None => panic!(
+            "{parser}: there is no context of type {}",
+            std::any::type_name::<C>()
+        ),
+    }
+}
+
+impl<'a, C: 'static, E: 'a, F: 'a, I: ?Sized> Parser<'a, Rc<C>, E, F, I> {
+    /// Returns the current context of type `C` (see `Session::set_context`).
+    ///
+    /// # Panics
+    /// When there is no session, or no context of that type in it.
+    pub fn context() -> Self {
+        Parser::from_fn(|state| ParseResult::Ok(context_of::<C, _>(&state, "context"), state.pos))
+            .with_name(format!("context::<{}>", std::any::type_name::<C>()))
+    }
+}
+
+impl<'a, E: 'a, F: 'a, I: ?Sized> Parser<'a, (), E, F, I> {
+    /// Replaces the context of type `C`. Like everything in the session, this
+    /// is undone if a parser around it fails and backtracks.
+    ///
+    /// # Panics
+    /// When there is no session.
+    pub fn set_context<C: 'static>(context: C) -> Self {
+        let context = Rc::new(context);
+        Parser::from_fn(move |state| {
+            session_of(&state, "set_context").replace_context(context.clone());
+            ParseResult::Ok((), state.pos)
+        })
+        .with_name(format!("set_context::<{}>", std::any::type_name::<C>()))
+    }
+
+    /// Changes the context of type `C` with `f`. This clones the context, so
+    /// for big contexts prefer data structures that are cheap to clone.
+    ///
+    /// # Panics
+    /// When there is no session, or no context of that type in it.
+    pub fn update_context<C: Clone + 'static>(f: impl Fn(&mut C) + 'a) -> Self {
+        Parser::from_fn(move |state| {
+            let mut context = C::clone(&context_of(&state, "update_context"));
+            f(&mut context);
+            session_of(&state, "update_context").replace_context(Rc::new(context));
+            ParseResult::Ok((), state.pos)
+        })
+        .with_name(format!("update_context::<{}>", std::any::type_name::<C>()))
+    }
+}
+
+impl<'a, T: 'a, E: 'a, F: 'a, I: ?Sized> Parser<'a, T, E, F, I> {
+    /// Runs this parser with the context of type `C` changed by `f`, and puts
+    /// the old context back after it is done.
+    ///
+    /// # Panics
+    /// When there is no session, or no context of that type in it.
+    pub fn local_context<C: Clone + 'static>(self, f: impl Fn(&mut C) + 'a) -> Self {
+        let name = format!("local_context({})", self.name);
+        let grammar = self.grammar.clone();
+        Parser::from_fn(move |state| {
+            let session = session_of(&state, "local_context");
+            let old = context_of::<C, _>(&state, "local_context");
+            let mut context = C::clone(&old);
+            f(&mut context);
+            session.replace_context(Rc::new(context));
+            let result = self.parse(state);
+            // Putting back the old value (and not rolling back) keeps any
+            // other changes that were made in the meantime.
+            if let ParseResult::Ok(..) | ParseResult::Err(..) = result {
+                session.replace_context(old);
+            }
+            result
+        })
+        .with_name(name)
+        .described(grammar)
+    }
+}
@@ -0,0 +1,250 @@
+use crate::parser::ParseResult;
+use crate::position::Span;
+
+use std::fmt;
+
+/// How bad a diagnostic is. This decides the header and the colors.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// A secondary piece of source code to point at, with a message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(span: impl Into<Span>, message: impl Into<String>) -> Self {
+        let span = span.into();
+        let message = message.into();
+        Label { span, message }
+    }
+}
+
+/// Implement this for your error type to render it with `Report`.
+/// Only `message` is required.
+pub trait Diagnostic {
+    /// The main message, shown in the header.
+    fn message(&self) -> String;
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    /// An error code, like `E0308`.
+    fn code(&self) -> Option<String> {
+        None
+    }
+
+    /// A short message shown next to the caret underline.
+    fn label(&self) -> Option<String> {
+        None
+    }
+
+    /// More places in the source that are related to the error.
+    fn labels(&self) -> Vec<Label> {
+        vec![]
+    }
+
+    /// Notes shown at the bottom of the report.
+    fn notes(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const CYAN: &str = "\x1b[1;36m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// A rendering of a diagnostic over the source code it happened in, in the
+/// style of rustc:
+///
+/// ```text
+/// error: expected a digit
+///  --> main.x:1:5
+///   |
+/// 1 | 12 + x
+///   |      ^ this is not a digit
+/// ```
+///
+/// `Report` implements `Display`, so render it with `to_string` or `{}`.
+#[derive(Debug, Clone)]
+pub struct Report<'a, D: ?Sized> {
+    source: &'a str,
+    span: Span,
+    diagnostic: &'a D,
+    file_name: Option<String>,
+    color: bool,
+}
+
+impl<'a, D: Diagnostic + ?Sized> Report<'a, D> {
+    pub fn new(source: &'a str, at: impl Into<Span>, diagnostic: &'a D) -> Self {
+        Report {
+            source,
+            span: at.into(),
+            diagnostic,
+            file_name: None,
+            color: false,
+        }
+    }
+
+    /// Shows this file name in the location line.
+    pub fn with_file_name(self, file_name: impl Into<String>) -> Self {
+        let file_name = Some(file_name.into());
+        Report { file_name, ..self }
+    }
+
+    /// Uses ANSI escape codes to color the output.
+    pub fn with_color(self, color: bool) -> Self {
+        Report { color, ..self }
+    }
+
+    fn paint(&self, style: &'static str) -> &'static str {
+        if self.color {
+            style
+        } else {
+            ""
+        }
+    }
+
+    fn severity_style(&self) -> &'static str {
+        self.paint(match self.diagnostic.severity() {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Note => CYAN,
+        })
+    }
+}
+
+impl<D: Diagnostic + ?Sized> fmt::Display for Report<'_, D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let diagnostic = self.diagnostic;
+        let (severity, gutter) = (self.severity_style(), self.paint(BLUE));
+        let (bold, reset) = (self.paint(BOLD), self.paint(RESET));
+
+        // The header.
+        write!(f, "{severity}{}", diagnostic.severity())?;
+        if let Some(code) = diagnostic.code() {
+            write!(f, "[{code}]")?;
+        }
+        writeln!(f, "{reset}{bold}: {}{reset}", diagnostic.message())?;
+
+        // All of the labels, with the primary one marked by `true`.
+        let primary = Label::new(self.span, diagnostic.label().unwrap_or_default());
+        let mut labels: Vec<(Label, bool)> = vec![(primary, true)];
+        labels.extend(diagnostic.labels().into_iter().map(|l| (l, false)));
+        labels.sort_by_key(|(l, _)| l.span.start.offset);
+
+        let (line, col) = line_col(self.source, self.span.start.offset);
+        let max_line = labels
+            .iter()
+            .map(|(l, _)| line_col(self.source, l.span.start.offset).0)
+            .max()
+            .unwrap_or(line);
+        let width = max_line.to_string().len();
+        let pad = " ".repeat(width);
+
+        // The location.
+        write!(f, "{pad}{gutter}-->{reset} ")?;
+        if let Some(file_name) = &self.file_name {
+            write!(f, "{file_name}:")?;
+        }
+        writeln!(f, "{line}:{col}")?;
+        writeln!(f, "{pad} {gutter}|{reset}")?;
+
+        // The source lines, each followed by the underlines of its labels.
+        let mut last_line = None;
+        for (label, is_primary) in &labels {
+            let at = label.span.start.offset.min(self.source.len());
+            let (line, _) = line_col(self.source, at);
+            let (start, text) = line_at(self.source, at);
+            if last_line != Some(line) {
+                if last_line.is_some_and(|last| line > last + 1) {
+                    writeln!(f, "{gutter}...{reset}")?;
+                }
+                writeln!(f, "{gutter}{line:>width$} |{reset} {text}")?;
+                last_line = Some(line);
+            }
+            let before = &self.source[start..at];
+            // Multi-line spans are only underlined until the end of their
+            // first line.
+            let end = label.span.end.offset.min(start + text.len()).max(at);
+            let underlined = self.source[at..end].chars().count();
+            let (mark, style) = if *is_primary {
+                ('^', severity)
+            } else {
+                ('-', gutter)
+            };
+            let mut underline = String::new();
+            for ch in before.chars() {
+                underline.push(if ch == '\t' { '\t' } else { ' ' });
+            }
+            underline.extend(std::iter::repeat_n(mark, underlined.max(1)));
+            write!(f, "{pad} {gutter}|{reset} {style}{underline}")?;
+            if !label.message.is_empty() {
+                write!(f, " {}", label.message)?;
+            }
+            writeln!(f, "{reset}")?;
+        }
+
+        // The notes.
+        let notes = diagnostic.notes();
+        if !notes.is_empty() {
+            writeln!(f, "{pad} {gutter}|{reset}")?;
+        }
+        for note in notes {
+            writeln!(f, "{pad} {gutter}={reset} {bold}note{reset}: {note}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the 1-based line and column (in characters) of a byte offset.
+fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let (start, _) = line_at(source, offset);
+    let line = 1 + source[..start].matches('\n').count();
+    let col = 1 + source[start..offset].chars().count();
+    (line, col)
+}
+
+/// Returns the offset where the line of `offset` starts, and the text of the
+/// line (without the newline).
+fn line_at(source: &str, offset: usize) -> (usize, &str) {
+    let offset = offset.min(source.len());
+    let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i);
+    let text = source[start..end].trim_end_matches('\r');
+    (start, text)
+}
+
+impl<T, E: Diagnostic, F> ParseResult<T, E, F> {
+    /// Returns a report of the error, if this result is an error.
+    pub fn report<'a>(&'a self, source: &'a str) -> Option<Report<'a, E>> {
+        match self {
+            ParseResult::Err(e, pos) => Some(Report::new(source, *pos, e)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::input::Input;
+use crate::parser::ParseResult;
+use crate::state::State;
+
+use std::io::{self, Read};
+
+/// Parses messages one after the other from a `Read`er (like a socket or a
+/// pipe), reading more only when a parser asks for it with
+/// `ParseResult::Incomplete`.
+///
+/// Every message is parsed from the start of the bytes that were not used
+/// yet, so the positions in a result are from the start of its message.
+#[derive(Debug)]
+pub struct Driver<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    chunk_size: usize,
+    /// Did the reader end?
+    done: bool,
+}
+
+impl<R: Read> Driver<R> {
+    pub fn new(reader: R) -> Self {
+        Driver {
+            reader,
+            buffer: vec![],
+            chunk_size: 4096,
+            done: false,
+        }
+    }
+
+    /// Sets how many bytes to try to read at a time.
+    pub fn with_chunk_size(self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "with_chunk_size: chunk size must not be 0");
+        Driver { chunk_size, ..self }
+    }
+
+    /// The bytes that were read, but are not a part of a message yet.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Parses the next message as text, with `parse` (usually something like
+    /// `|state| message().parse(state)`). Returns `None` when the reader
+    /// ended and every byte was used.
+    ///
+    /// When the result is not `Ok`, nothing is used, so it is up to the
+    /// caller to give up or to skip some bytes and try again.
+    pub fn next<T, E, F>(
+        &mut self,
+        parse: impl for<'b> Fn(State<'b>) -> ParseResult<T, E, F>,
+    ) -> io::Result<Option<ParseResult<T, E, F>>> {
+        self.next_from(text_prefix, parse)
+    }
+
+    /// Like `next`, but parses bytes.
+    pub fn next_bytes<T, E, F>(
+        &mut self,
+        parse: impl for<'b> Fn(State<'b, [u8]>) -> ParseResult<T, E, F>,
+    ) -> io::Result<Option<ParseResult<T, E, F>>> {
+        self.next_from(|bytes, _| Ok(bytes), parse)
+    }
+
+    fn next_from<I: ?Sized + Input, T, E, F>(
+        &mut self,
+        view: impl Fn(&[u8], bool) -> io::Result<&I>,
+        parse: impl for<'b> Fn(State<'b, I>) -> ParseResult<T, E, F>,
+    ) -> io::Result<Option<ParseResult<T, E, F>>> {
+        loop {
+            if self.done && self.buffer.is_empty() {
+                return Ok(None);
+            }
+            let partial = !self.done;
+            let state = State::of(view(&self.buffer, partial)?).with_partial(partial);
+            match parse(state) {
+                ParseResult::Incomplete(needed) if !self.done => self.read_at_least(needed)?,
+                ParseResult::Ok(value, pos) => {
+                    self.buffer.drain(..pos.offset);
+                    return Ok(Some(ParseResult::Ok(value, pos)));
+                }
+                result => return Ok(Some(result)),
+            }
+        }
+    }
+
+    /// Reads until there are `needed` more bytes, or the reader ends.
+    fn read_at_least(&mut self, needed: usize) -> io::Result<()> {
+        let target = self.buffer.len() + needed.max(1);
+        let mut chunk = vec![0; self.chunk_size];
+        while self.buffer.len() < target {
+            match self.reader.read(&mut chunk) {
+                Ok(0) => {
+                    self.done = true;
+                    break;
+                }
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the bytes as text. On a partial input, a character at the end that
+/// was cut in the middle is left out (the rest of it was not read yet).
+fn text_prefix(bytes: &[u8], partial: bool) -> io::Result<&str> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok(text),
+        Err(e) if partial && e.error_len().is_none() => {
+            Ok(std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap())
+        }
+        Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+    }
+}
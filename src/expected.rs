@@ -0,0 +1,139 @@
+use crate::diagnostic::Diagnostic;
+use crate::parser::{ParseResult, Parser};
+use crate::position::Pos;
+use crate::state::State;
+use crate::{CombineFail, CombineManyFail};
+
+use std::fmt;
+
+/// A failure that remembers what was expected, and where.
+/// When two of these are combined (by `or` or `one_of`), the one that got
+/// further into the input wins, and ties merge their labels. This gives
+/// messages like "expected identifier or number, found '+'".
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Expected {
+    /// What was expected, in the order the alternatives were tried.
+    pub labels: Vec<String>,
+    /// Where it was expected.
+    pub pos: Pos,
+    /// The character that was found instead, or `None` at the end of input.
+    pub found: Option<char>,
+}
+
+impl Expected {
+    /// Expecting `label` at the current position of `state`.
+    pub fn at(label: impl Into<String>, state: State) -> Self {
+        Expected {
+            labels: vec![label.into()],
+            pos: state.pos,
+            found: state.rest().chars().next(),
+        }
+    }
+
+    /// Keeps the failure that got further, or merges them if they are at
+    /// the same position.
+    pub fn merge(mut self, other: Expected) -> Expected {
+        if other.pos.offset > self.pos.offset {
+            return other;
+        }
+        if other.pos.offset == self.pos.offset {
+            for label in other.labels {
+                if !self.labels.contains(&label) {
+                    self.labels.push(label);
+                }
+            }
+        }
+        self
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected ")?;
+        match self.labels.as_slice() {
+            [] => write!(f, "nothing")?,
+            [only] => write!(f, "{only}")?,
+            [init @ .., last] => write!(f, "{} or {last}", init.join(", "))?,
+        }
+        match self.found {
+            Some(ch) => write!(f, ", found {ch:?}"),
+            None => write!(f, ", found end of input"),
+        }
+    }
+}
+
+impl Diagnostic for Expected {
+    fn message(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl<'a, I: ?Sized> CombineFail<'a, Expected, Expected, I> for Expected {
+    fn combine_fail(self, _: State<'a, I>, other: Expected, _: State<'a, I>) -> Expected {
+        self.merge(other)
+    }
+}
+
+/// For `repeat_until`, where there may be no failure of an item to merge.
+impl<'a, I: ?Sized> CombineFail<'a, Option<Expected>, Expected, I> for Expected {
+    fn combine_fail(self, _: State<'a, I>, other: Option<Expected>, _: State<'a, I>) -> Expected {
+        match other {
+            Some(other) => self.merge(other),
+            None => self,
+        }
+    }
+}
+
+/// For `then`, where only one of the two parsers fails.
+impl<'a, I: ?Sized> CombineFail<'a, Option<Expected>, Expected, I> for Option<Expected> {
+    fn combine_fail(self, _: State<'a, I>, other: Option<Expected>, _: State<'a, I>) -> Expected {
+        match (self, other) {
+            (Some(this), Some(other)) => this.merge(other),
+            (this, other) => this.or(other).unwrap_or_default(),
+        }
+    }
+}
+
+impl<'a, I: ?Sized> CombineManyFail<'a, Expected, I> for Expected {
+    fn combine_many_fail(fails: Vec<(Self, State<'a, I>)>) -> Expected {
+        fails
+            .into_iter()
+            .map(|(f, _)| f)
+            .reduce(Expected::merge)
+            .unwrap_or_default()
+    }
+}
+
+impl<'a, T, E, F> Parser<'a, T, E, F> {
+    /// Names what this parser expects. When it fails where it started, the
+    /// failure is replaced by an `Expected` with this label there. When it
+    /// got further before it failed, the `Expected` is where it failed, so
+    /// the furthest failure is kept. Parsers like `char_eq` fail after the
+    /// character they reject, so a failure there counts as where it started.
+    pub fn label(self, label: impl Into<String>) -> Parser<'a, T, E, Expected>
+    where
+        T: 'a,
+        E: 'a,
+        F: 'a,
+    {
+        let label = label.into();
+        let name = format!("label({}, {label:?})", self.name);
+        let grammar = self.grammar.clone();
+        Parser::from_fn(move |state| match self.parse(state) {
+            ParseResult::Ok(value, pos) => ParseResult::Ok(value, pos),
+            ParseResult::Fail(_, pos) => {
+                let first = state.next_char().map_or(state.pos, |(_, next)| next);
+                let pos = if pos.offset <= first.offset {
+                    state.pos
+                } else {
+                    pos
+                };
+                ParseResult::Fail(Expected::at(&label, state.with_pos(pos)), pos)
+            }
+            ParseResult::Err(err_value, pos) => ParseResult::Err(err_value, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+        })
+        .with_name(name)
+        .described(grammar)
+    }
+}
@@ -0,0 +1,363 @@
+use crate::parser::Parser;
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::rc::Rc;
+
+/// A description of what a parser parses, made of the combinators it was
+/// built with. Get one with `Parser::describe`, and turn it into reference
+/// docs with `to_ebnf` or `to_dot`.
+///
+/// Parsers that take a closure (like `and_then`) can't be looked into, so
+/// they are described by their name as a `Terminal`. Use the combinators
+/// that take parsers (like `or` and `repeat_0`), or describe a parser by
+/// hand with `Parser::with_grammar`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Grammar {
+    /// Text that is matched as is.
+    Literal(String),
+    /// Anything that is not described further, like a `letter`.
+    Terminal(String),
+    /// One after the other. An empty sequence matches nothing.
+    Seq(Vec<Grammar>),
+    /// The first one of these that matches.
+    Alt(Vec<Grammar>),
+    Repeat {
+        inner: Box<Grammar>,
+        min: usize,
+        max: Option<usize>,
+    },
+    /// A rule (see `Parser::rule`). Where it is used, only its name is shown,
+    /// and the definition is shown once, on its own.
+    Rule(RuleName, Rc<Grammar>),
+    /// A use of a rule that is defined somewhere else, like through a
+    /// `ForwardDecl`.
+    Ref(RuleName),
+}
+
+/// The name of a rule. Rules are told apart by their `RuleName`, and not by
+/// the text of the name, and a rule that is used before it is defined gets
+/// its name when it is defined (see `ForwardDecl::define`).
+#[derive(Debug, Clone)]
+pub struct RuleName(Rc<RefCell<String>>);
+
+impl RuleName {
+    pub fn new(name: impl Into<String>) -> Self {
+        RuleName(Rc::new(RefCell::new(name.into())))
+    }
+
+    pub fn get(&self) -> String {
+        self.0.borrow().clone()
+    }
+
+    pub(crate) fn set(&self, name: String) {
+        *self.0.borrow_mut() = name;
+    }
+}
+
+impl PartialEq for RuleName {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for RuleName {}
+
+impl Grammar {
+    /// Returns the alternatives `options`, where alternatives inside of them
+    /// are flattened (`(a | b) | c` is `a | b | c`).
+    pub(crate) fn alt(options: impl IntoIterator<Item = Grammar>) -> Grammar {
+        let mut flat = vec![];
+        for option in options {
+            match option {
+                Grammar::Alt(inner) => flat.extend(inner),
+                option => flat.push(option),
+            }
+        }
+        Grammar::Alt(flat)
+    }
+
+    /// Returns the sequence `items`, where sequences inside of it are
+    /// flattened (`(a, b), c` is `a, b, c`).
+    pub(crate) fn seq(items: impl IntoIterator<Item = Grammar>) -> Grammar {
+        let mut flat = vec![];
+        for item in items {
+            match item {
+                Grammar::Seq(inner) => flat.extend(inner),
+                item => flat.push(item),
+            }
+        }
+        Grammar::Seq(flat)
+    }
+
+    /// Makes `definition` the rule `rule`, for rules that are used (through
+    /// `Grammar::Ref`) before they are defined. The rule takes the name of
+    /// `definition` if it is a rule already, and `name` otherwise.
+    pub(crate) fn define(rule: RuleName, definition: Grammar, name: &str) -> Grammar {
+        match definition {
+            Grammar::Rule(old, definition) => {
+                rule.set(old.get());
+                Grammar::Rule(rule, definition)
+            }
+            definition => {
+                rule.set(name.to_string());
+                Grammar::Rule(rule, Rc::new(definition))
+            }
+        }
+    }
+
+    /// Returns every rule that this grammar uses (and this grammar itself if
+    /// it is a rule), in the order they first show up.
+    fn rules(&self) -> Vec<(&RuleName, &Grammar)> {
+        fn visit<'g>(
+            grammar: &'g Grammar,
+            seen: &mut HashSet<*const RefCell<String>>,
+            rules: &mut Vec<(&'g RuleName, &'g Grammar)>,
+        ) {
+            match grammar {
+                Grammar::Literal(_) | Grammar::Terminal(_) | Grammar::Ref(_) => {}
+                Grammar::Seq(items) | Grammar::Alt(items) => {
+                    items.iter().for_each(|item| visit(item, seen, rules))
+                }
+                Grammar::Repeat { inner, .. } => visit(inner, seen, rules),
+                Grammar::Rule(name, definition) => {
+                    if seen.insert(Rc::as_ptr(&name.0)) {
+                        rules.push((name, definition));
+                        visit(definition, seen, rules);
+                    }
+                }
+            }
+        }
+        let mut rules = vec![];
+        visit(self, &mut HashSet::new(), &mut rules);
+        rules
+    }
+
+    /// Returns the grammar in EBNF, with a line for every rule:
+    /// `list = "[", [ item, { ",", item } ], "]" ;`. Things that are not
+    /// described are special sequences, like `? letter ?`, and so is text
+    /// that an EBNF string can't hold, escaped like a Rust string:
+    /// `? "\n" ?`. When this grammar is not a rule, it is shown as a rule
+    /// called `start`.
+    pub fn to_ebnf(&self) -> String {
+        let mut ebnf = String::new();
+        if !matches!(self, Grammar::Rule(..)) {
+            writeln!(ebnf, "start = {} ;", self.ebnf(Precedence::Alt)).unwrap();
+        }
+        for (name, definition) in self.rules() {
+            let definition = definition.ebnf(Precedence::Alt);
+            writeln!(ebnf, "{} = {definition} ;", name.get()).unwrap();
+        }
+        ebnf
+    }
+
+    fn ebnf(&self, outer: Precedence) -> String {
+        match self {
+            Grammar::Literal(text) if needs_escapes(text) => {
+                // EBNF strings have no escapes, so the text is shown escaped
+                // in a special sequence, which can't hold a `?`.
+                let escaped = format!("{text:?}").replace('?', "\\u{3f}");
+                format!("? {escaped} ?")
+            }
+            Grammar::Literal(text) if text.contains('"') => format!("'{text}'"),
+            Grammar::Literal(text) => format!("\"{text}\""),
+            Grammar::Terminal(name) => format!("? {name} ?"),
+            Grammar::Seq(items) if items.is_empty() => "( )".to_string(),
+            Grammar::Seq(items) if items.len() == 1 => items[0].ebnf(outer),
+            Grammar::Seq(items) => {
+                let items: Vec<_> = items
+                    .iter()
+                    .map(|item| item.ebnf(Precedence::Seq))
+                    .collect();
+                parenthesize(items.join(", "), outer > Precedence::Alt)
+            }
+            Grammar::Alt(options) => {
+                let is_empty = |g: &&Grammar| matches!(g, Grammar::Seq(items) if items.is_empty());
+                let (empty, options): (Vec<_>, Vec<_>) = options.iter().partition(is_empty);
+                if let ([], [option]) = (empty.as_slice(), options.as_slice()) {
+                    return option.ebnf(outer);
+                }
+                let options: Vec<_> = options.iter().map(|g| g.ebnf(Precedence::Alt)).collect();
+                match (empty.is_empty(), options.len()) {
+                    (true, _) => parenthesize(options.join(" | "), outer > Precedence::Alt),
+                    // An empty option makes the others optional.
+                    (false, _) => format!("[ {} ]", options.join(" | ")),
+                }
+            }
+            Grammar::Repeat { inner, min, max } => {
+                // Items that repeat a number of times use repetition
+                // factors, like `3 * x`, and are `factored`.
+                if (*min, *max) == (1, Some(1)) {
+                    return inner.ebnf(outer);
+                }
+                let mut items = vec![];
+                match min {
+                    0 => {}
+                    1 => items.push((inner.ebnf(Precedence::Seq), false)),
+                    min => {
+                        items.push((format!("{min} * {}", inner.ebnf(Precedence::Factor)), true))
+                    }
+                }
+                let inner = inner.ebnf(Precedence::Alt);
+                match max.map(|max| max.saturating_sub(*min)) {
+                    None => items.push((format!("{{ {inner} }}"), false)),
+                    Some(0) => {}
+                    Some(1) => items.push((format!("[ {inner} ]"), false)),
+                    Some(optional) => items.push((format!("{optional} * [ {inner} ]"), true)),
+                }
+                match items.as_slice() {
+                    [] => "( )".to_string(),
+                    [(item, factored)] => {
+                        parenthesize(item.clone(), *factored && outer > Precedence::Seq)
+                    }
+                    items => {
+                        let items: Vec<_> = items.iter().map(|(item, _)| item.as_str()).collect();
+                        parenthesize(items.join(", "), outer > Precedence::Alt)
+                    }
+                }
+            }
+            Grammar::Rule(name, _) | Grammar::Ref(name) => name.get(),
+        }
+    }
+
+    /// Returns the grammar as a DOT graph (for Graphviz). Every rule is a
+    /// box with an arrow to its definition, and uses of a rule point back at
+    /// the box, so recursive rules make cycles.
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot {
+            text: String::new(),
+            nodes: 0,
+            rules: vec![],
+        };
+        dot.text.push_str("digraph grammar {\n");
+        let top = dot.node(self);
+        if !matches!(self, Grammar::Rule(..)) {
+            writeln!(
+                dot.text,
+                "  start [shape=box, style=bold];\n  start -> {top};"
+            )
+            .unwrap();
+        }
+        for (name, definition) in self.rules() {
+            let rule = dot.rule(name);
+            let definition = dot.node(definition);
+            writeln!(dot.text, "  {rule} -> {definition};").unwrap();
+        }
+        dot.text.push_str("}\n");
+        dot.text
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Alt,
+    Seq,
+    /// What a repetition factor repeats, like `x` in `3 * x`.
+    Factor,
+}
+
+/// Whether `text` can't be written as an EBNF string: it has both kinds of
+/// quotes, or a control character like a newline.
+fn needs_escapes(text: &str) -> bool {
+    (text.contains('"') && text.contains('\'')) || text.chars().any(char::is_control)
+}
+
+fn parenthesize(text: String, yes: bool) -> String {
+    if yes {
+        format!("( {text} )")
+    } else {
+        text
+    }
+}
+
+struct Dot {
+    text: String,
+    nodes: usize,
+    /// The rules that have a node, and its id.
+    rules: Vec<(RuleName, String)>,
+}
+
+impl Dot {
+    /// Returns the id of the node of a rule, and adds it if it is new.
+    fn rule(&mut self, name: &RuleName) -> String {
+        if let Some((_, id)) = self.rules.iter().find(|(rule, _)| rule == name) {
+            return id.clone();
+        }
+        let id = format!("rule{}", self.rules.len());
+        let label = dot_string(&name.get());
+        writeln!(self.text, "  {id} [label={label}, shape=box, style=bold];").unwrap();
+        self.rules.push((name.clone(), id.clone()));
+        id
+    }
+
+    /// Adds the nodes of `grammar`, and returns the id of its top node.
+    fn node(&mut self, grammar: &Grammar) -> String {
+        let (label, shape, children): (String, _, &[Grammar]) = match grammar {
+            Grammar::Rule(name, _) | Grammar::Ref(name) => return self.rule(name),
+            Grammar::Literal(text) => (format!("\"{text}\""), "ellipse", &[]),
+            Grammar::Terminal(name) => (name.clone(), "plaintext", &[]),
+            Grammar::Seq(items) => ("seq".to_string(), "circle", items),
+            Grammar::Alt(options) => ("alt".to_string(), "diamond", options),
+            Grammar::Repeat { inner, min, max } => {
+                let max = max.map_or(String::new(), |max| max.to_string());
+                (
+                    format!("{min}..{max}"),
+                    "circle",
+                    std::slice::from_ref(&**inner),
+                )
+            }
+        };
+        let id = format!("n{}", self.nodes);
+        self.nodes += 1;
+        let label = dot_string(&label);
+        writeln!(self.text, "  {id} [label={label}, shape={shape}];").unwrap();
+        for (i, child) in children.iter().enumerate() {
+            let child = self.node(child);
+            if let Grammar::Seq(_) = grammar {
+                writeln!(self.text, "  {id} -> {child} [label={}];", i + 1).unwrap();
+            } else {
+                writeln!(self.text, "  {id} -> {child};").unwrap();
+            }
+        }
+        id
+    }
+}
+
+fn dot_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl<'a, T, E, F, I: ?Sized> Parser<'a, T, E, F, I> {
+    /// Describes what this parser parses. See `Grammar`.
+    pub fn describe(&self) -> Grammar {
+        match &self.grammar {
+            Some(grammar) => Grammar::clone(grammar),
+            None => Grammar::Terminal(self.name.to_string()),
+        }
+    }
+
+    /// Sets the description of this parser, for `describe`.
+    pub fn with_grammar(mut self, grammar: Grammar) -> Self {
+        self.grammar = Some(Rc::new(grammar));
+        self
+    }
+
+    /// Names this parser, and makes it a rule of the grammar, so `describe`
+    /// shows it by name where it is used. If this parser is a rule already,
+    /// the new rule is defined as that rule, which keeps its own name. To
+    /// name a rule that is used before it is defined, name the definition
+    /// that is given to `ForwardDecl::define`.
+    pub fn rule(self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        let grammar = Grammar::Rule(RuleName::new(name.clone()), Rc::new(self.describe()));
+        self.with_name(name).with_grammar(grammar)
+    }
+
+    /// Sets the description of this parser, for combinators that keep the
+    /// description of the parser they wrap (like `map`).
+    pub(crate) fn described(mut self, grammar: Option<Rc<Grammar>>) -> Self {
+        self.grammar = grammar;
+        self
+    }
+}
@@ -0,0 +1,141 @@
+use crate::parser::{ParseResult, Parser};
+use crate::position::{Col, Pos, Row};
+use crate::state::State;
+
+/// The failure of a parser that started at the wrong place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BadIndent<F> {
+    /// It should have started at column `col`.
+    NotAligned { col: Col, found: Pos },
+    /// It should have started after column `col`.
+    NotIndented { col: Col, found: Pos },
+    /// It should have started on row `row`.
+    NotOnRow { row: Row, found: Pos },
+    /// It started at the right place, but failed.
+    Fail(F),
+}
+
+impl<'a, E: 'a, F: 'a, I: ?Sized> Parser<'a, Pos, E, F, I> {
+    /// Returns the current position, to check the indentation of later
+    /// parsers against (with `aligned_with`, `indented` and `same_line`).
+    pub fn checkpoint_indent() -> Self {
+        Parser::from_fn(|state| ParseResult::Ok(state.pos, state.pos))
+            .with_name("checkpoint_indent")
+    }
+}
+
+impl<'a, T: 'a, E: 'a, F: 'a, I: ?Sized> Parser<'a, T, E, F, I> {
+    /// Runs this parser only if `check` accepts the position it starts at.
+    fn starting_where(
+        self,
+        name: String,
+        check: impl Fn(Pos) -> Option<BadIndent<F>> + 'a,
+    ) -> Parser<'a, T, E, BadIndent<F>, I> {
+        Parser::from_fn(move |state| {
+            if let Some(bad) = check(state.pos) {
+                return ParseResult::Fail(bad, state.pos);
+            }
+            match self.parse(state) {
+                ParseResult::Ok(value, pos) => ParseResult::Ok(value, pos),
+                ParseResult::Fail(f, pos) => ParseResult::Fail(BadIndent::Fail(f), pos),
+                ParseResult::Err(e, pos) => ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+            }
+        })
+        .with_name(name)
+    }
+
+    /// Fails unless this parser starts exactly at column `col`.
+    pub fn aligned_with(self, col: Col) -> Parser<'a, T, E, BadIndent<F>, I> {
+        let name = format!("aligned_with({}, {col})", self.name);
+        self.starting_where(name, move |found| {
+            (found.col != col).then_some(BadIndent::NotAligned { col, found })
+        })
+    }
+
+    /// Fails unless this parser starts to the right of column `col`.
+    pub fn indented(self, col: Col) -> Parser<'a, T, E, BadIndent<F>, I> {
+        let name = format!("indented({}, {col})", self.name);
+        self.starting_where(name, move |found| {
+            (found.col <= col).then_some(BadIndent::NotIndented { col, found })
+        })
+    }
+
+    /// Fails unless this parser starts on row `row`.
+    pub fn same_line(self, row: Row) -> Parser<'a, T, E, BadIndent<F>, I> {
+        let name = format!("same_line({}, {row})", self.name);
+        self.starting_where(name, move |found| {
+            (found.row != row).then_some(BadIndent::NotOnRow { row, found })
+        })
+    }
+}
+
+impl<'a, T: 'a, E: 'a, F: 'a> Parser<'a, T, E, F> {
+    /// Parses one or more items, separated by whitespace, that all start at
+    /// the column of the first item. This is a block in the style of Python,
+    /// YAML or Elm. The block ends at the first item that is not aligned
+    /// (the whitespace before it is not consumed).
+    pub fn block(self) -> Parser<'a, Vec<T>, E, F> {
+        let name = format!("block({})", self.name);
+        Parser::from_fn(move |state: State<'a>| {
+            let col = state.pos.col;
+            let (mut items, mut end) = match self.parse(state) {
+                // Like in `repeat_0`, an item that consumed nothing is the last.
+                ParseResult::Ok(x, pos) if pos == state.pos => {
+                    return ParseResult::Ok(vec![x], pos)
+                }
+                ParseResult::Ok(x, pos) => (vec![x], pos),
+                ParseResult::Fail(f, pos) => return ParseResult::Fail(f, pos),
+                ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+            };
+            let this = self.clone();
+            let item = Parser::skip_whitespace().and_then(move |()| this.clone().aligned_with(col));
+            loop {
+                match item.parse(state.with_pos(end)) {
+                    ParseResult::Ok(x, pos) if pos == end => {
+                        items.push(x);
+                        break;
+                    }
+                    ParseResult::Ok(x, pos) => {
+                        items.push(x);
+                        end = pos;
+                    }
+                    ParseResult::Fail(..) => break,
+                    ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                    ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+                }
+            }
+            ParseResult::Ok(items, end)
+        })
+        .with_name(name)
+    }
+
+    /// A line fold, like in Haskell: a construct that may continue on the
+    /// following lines, as long as they are indented more than the line it
+    /// started on. `f` gets a whitespace parser to use between the parts of
+    /// the construct, that fails when it would move to a line that is not
+    /// indented enough.
+    pub fn line_fold(
+        f: impl Fn(Parser<'a, (), E, BadIndent<()>>) -> Parser<'a, T, E, F> + 'a,
+    ) -> Self {
+        Parser::from_fn(move |state: State<'a>| {
+            let start = state.pos;
+            let space = Parser::skip_whitespace().and_then(move |()| {
+                Parser::checkpoint_indent().and_then(move |pos| {
+                    if pos.row == start.row || pos.col > start.col {
+                        Parser::ret(())
+                    } else {
+                        let bad = BadIndent::NotIndented {
+                            col: start.col,
+                            found: pos,
+                        };
+                        Parser::fail(bad)
+                    }
+                })
+            });
+            f(space).parse(state)
+        })
+        .with_name("line_fold")
+    }
+}
@@ -0,0 +1,91 @@
+use crate::position::{ColumnMode, Pos};
+
+use unicode_segmentation::GraphemeCursor;
+
+/// Something that parsers can run over: source text (`str`), bytes (`[u8]`),
+/// a slice of any other items (like the tokens that a `Lexer` makes), or a
+/// custom stream.
+///
+/// Positions into an input are offsets in the units of `Input::len` (bytes
+/// for text, items for slices). An input may count rows and columns however
+/// it likes, as long as the offset only moves forward.
+pub trait Input {
+    /// A single element of the input.
+    type Item<'a>: Clone
+    where
+        Self: 'a;
+
+    /// A part of the input, as returned by `Parser::recognize`.
+    type Slice<'a>
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the part of the input from offset `start` to offset `end`.
+    fn slice(&self, start: usize, end: usize) -> Self::Slice<'_>;
+
+    /// Returns the item at `pos`, and the position right after it.
+    fn next_item(&self, pos: Pos, column_mode: ColumnMode) -> Option<(Self::Item<'_>, Pos)>;
+}
+
+impl Input for str {
+    type Item<'a> = char;
+    type Slice<'a> = &'a str;
+
+    fn len(&self) -> usize {
+        str::len(self)
+    }
+
+    fn slice(&self, start: usize, end: usize) -> &str {
+        &self[start..end]
+    }
+
+    fn next_item(&self, pos: Pos, column_mode: ColumnMode) -> Option<(char, Pos)> {
+        let ch = self[pos.offset..].chars().next()?;
+        let new_pos = match column_mode {
+            ColumnMode::Graphemes if ch != '\n' && !at_grapheme_boundary(self, pos) => {
+                pos.extend(ch)
+            }
+            _ => pos.advance(ch),
+        };
+        Some((ch, new_pos))
+    }
+}
+
+fn at_grapheme_boundary(text: &str, pos: Pos) -> bool {
+    GraphemeCursor::new(pos.offset, text.len(), true)
+        .is_boundary(text, 0)
+        .unwrap_or(true)
+}
+
+/// A slice of items, like bytes or tokens. There are no lines in a slice, so
+/// only the offset of a position moves (it is the index of the next item).
+impl<T> Input for [T] {
+    type Item<'a>
+        = &'a T
+    where
+        T: 'a;
+    type Slice<'a>
+        = &'a [T]
+    where
+        T: 'a;
+
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn slice(&self, start: usize, end: usize) -> &[T] {
+        &self[start..end]
+    }
+
+    fn next_item(&self, pos: Pos, _: ColumnMode) -> Option<(&T, Pos)> {
+        let item = self.get(pos.offset)?;
+        let offset = pos.offset + 1;
+        Some((item, Pos { offset, ..pos }))
+    }
+}
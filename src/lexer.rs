@@ -0,0 +1,200 @@
+use crate::parser::{ParseResult, Parser};
+use crate::position::{Pos, Span};
+use crate::primitives::EofFailure;
+use crate::state::State;
+
+/// A token that a `Lexer` made: what kind of token it is, and where it is in
+/// the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Token<K> {
+    pub kind: K,
+    pub span: Span,
+}
+
+impl<K> Token<K> {
+    pub const fn new(kind: K, span: Span) -> Self {
+        Token { kind, span }
+    }
+
+    /// Returns the source text of this token.
+    pub fn text<'s>(&self, source: &'s str) -> &'s str {
+        &source[self.span.start.offset..self.span.end.offset]
+    }
+
+    /// Returns where the token at `pos` is in the source. A parser that runs
+    /// over tokens reports positions in tokens, so use this to get back to
+    /// the source (for a `Report`, for example). At the end of the tokens,
+    /// this is an empty span after the last token.
+    pub fn span_at(tokens: &[Token<K>], pos: Pos) -> Span {
+        match (tokens.get(pos.offset), tokens.last()) {
+            (Some(token), _) => token.span,
+            (None, Some(last)) => Span::at(last.span.end),
+            (None, None) => Span::default(),
+        }
+    }
+}
+
+/// The failure of a `Lexer` that got to text that no rule matches.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NoToken;
+
+/// A builder for a lexer, that splits source text into `Token`s.
+/// Add token rules with `token` and `literal`, text to throw away (like
+/// whitespace and comments) with `skip`, and call `build`.
+///
+/// At every position, the rule that matches the longest text wins, and if
+/// more than one rule matches the same length, the one that was added first
+/// wins. So keywords should be added before identifiers.
+pub struct Lexer<'a, K, E> {
+    rules: Vec<Parser<'a, K, E>>,
+    skips: Vec<Parser<'a, (), E>>,
+}
+
+impl<K, E> Default for Lexer<'_, K, E> {
+    fn default() -> Self {
+        Lexer {
+            rules: vec![],
+            skips: vec![],
+        }
+    }
+}
+
+impl<'a, K: 'a, E: 'a> Lexer<'a, K, E> {
+    pub fn new() -> Self {
+        Lexer::default()
+    }
+
+    /// Adds a rule that makes tokens of the kind it returns.
+    pub fn token<F: 'a>(mut self, rule: Parser<'a, K, E, F>) -> Self {
+        self.rules.push(rule.map_fail(|_| ()));
+        self
+    }
+
+    /// Adds a rule that makes a `kind` token from exactly `text`.
+    pub fn literal(self, text: &'static str, kind: K) -> Self
+    where
+        K: Clone,
+    {
+        let rule = Parser::<char, E, _>::expect_string(text).map(move |()| kind.clone());
+        self.token(rule.with_name(format!("literal({text:?})")))
+    }
+
+    /// Adds a rule for text that is not a token.
+    pub fn skip<T: 'a, F: 'a>(mut self, rule: Parser<'a, T, E, F>) -> Self {
+        self.skips.push(rule.map(|_| ()).map_fail(|_| ()));
+        self
+    }
+
+    /// Returns a parser that splits all of the text into tokens.
+    /// It fails with `NoToken` at the first position where no rule matches,
+    /// and stops at the first error of a rule.
+    pub fn build(self) -> Parser<'a, Vec<Token<K>>, E, NoToken> {
+        Parser::from_fn(move |mut state| {
+            let mut tokens = vec![];
+            while !state.eof() {
+                match self.skip_at(state) {
+                    ParseResult::Ok((), pos) if pos != state.pos => {
+                        state = state.with_pos(pos);
+                        continue;
+                    }
+                    ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                    ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+                    _ => {}
+                }
+                match self.token_at(state) {
+                    ParseResult::Ok(kind, pos) => {
+                        tokens.push(Token::new(kind, Span::new(state.pos, pos)));
+                        state = state.with_pos(pos);
+                    }
+                    ParseResult::Fail(NoToken, pos) => return ParseResult::Fail(NoToken, pos),
+                    ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                    ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+                }
+            }
+            // More text could continue the last token, or add more tokens.
+            if state.partial {
+                return ParseResult::Incomplete(1);
+            }
+            ParseResult::Ok(tokens, state.pos)
+        })
+        .with_name("lexer")
+    }
+
+    /// Runs the first skip rule that consumes anything.
+    fn skip_at(&self, state: State<'a>) -> ParseResult<(), E> {
+        for skip in &self.skips {
+            match skip.parse(state) {
+                ParseResult::Ok((), pos) if pos != state.pos => return ParseResult::Ok((), pos),
+                ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+                _ => {}
+            }
+        }
+        ParseResult::Fail((), state.pos)
+    }
+
+    /// Makes the longest token that any rule can make at `state`.
+    fn token_at(&self, state: State<'a>) -> ParseResult<K, E, NoToken> {
+        let checkpoint = state.checkpoint();
+        let mut longest: Option<(usize, Pos)> = None;
+        for (i, rule) in self.rules.iter().enumerate() {
+            match rule.parse(state) {
+                ParseResult::Ok(_, pos)
+                    if longest.is_none_or(|(_, end)| pos.offset > end.offset) =>
+                {
+                    longest = Some((i, pos));
+                }
+                ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+                _ => {}
+            }
+            // Only the rule that wins gets to change the session.
+            state.rollback(checkpoint.clone());
+        }
+        match longest {
+            Some((_, end)) if end == state.pos => ParseResult::Fail(NoToken, state.pos),
+            Some((i, _)) => match self.rules[i].parse(state) {
+                ParseResult::Ok(kind, pos) => ParseResult::Ok(kind, pos),
+                ParseResult::Fail((), pos) => ParseResult::Fail(NoToken, pos),
+                ParseResult::Err(e, pos) => ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+            },
+            None => ParseResult::Fail(NoToken, state.pos),
+        }
+    }
+}
+
+impl<'a, K: 'a, E: 'a> Parser<'a, &'a Token<K>, E, EofFailure, [Token<K>]> {
+    /// Parses any single token. This is the `char` of token streams.
+    pub fn token() -> Self {
+        Parser::item().with_name("token")
+    }
+}
+
+impl<'a, K: PartialEq + std::fmt::Debug + 'a, E: 'a, F: Default + 'a>
+    Parser<'a, &'a Token<K>, E, F, [Token<K>]>
+{
+    /// Parses a single token of kind `kind`. It fails at the start of the
+    /// token that is not of that kind.
+    pub fn token_eq(kind: K) -> Self {
+        let name = format!("token_eq({kind:?})");
+        Parser::satisfy(move |token: &&Token<K>| token.kind == kind).with_name(name)
+    }
+}
+
+impl<'a, U: 'a, E: 'a, F: Default + 'a, K: 'a> Parser<'a, U, E, F, [Token<K>]> {
+    /// Parses a single token that `f` returns a value for. This is useful
+    /// for tokens that carry data, like identifiers and numbers.
+    pub fn token_with(f: impl Fn(&K) -> Option<U> + 'a) -> Self {
+        Parser::from_fn(
+            move |state: State<'a, [Token<K>]>| match state.next_item() {
+                Some((token, new_pos)) => match f(&token.kind) {
+                    Some(value) => ParseResult::Ok(value, new_pos),
+                    None => ParseResult::Fail(F::default(), state.pos),
+                },
+                None => state.out_of_input(1, F::default()),
+            },
+        )
+        .with_name("token_with")
+    }
+}
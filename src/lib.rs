@@ -1,21 +1,101 @@
 mod position;
+mod input;
 mod state;
 mod combine_fail;
 mod parser;
 mod primitives;
+mod separated;
+mod sequence;
+mod precedence;
+mod diagnostic;
+mod expected;
+mod session;
+mod recovery;
+mod context;
+mod memo;
+mod recursive;
+mod indent;
+mod lexer;
+mod binary;
+mod driver;
+mod trace;
+mod profile;
+mod grammar;
 
 pub use position::{
     Col,
+    ColumnMode,
     Row,
     Pos,
+    Span,
+};
+pub use input::{
+    Input,
 };
 pub use state::{
     State,
 };
+pub use session::{
+    Checkpoint,
+    Session,
+};
 pub use parser::{
     ParseResult,
     Parser,
 };
+pub use primitives::{
+    TooFew,
+};
+pub use separated::{
+    Trailing,
+    TrailingSeparator,
+};
+pub use sequence::{
+    Sequence,
+};
+pub use precedence::{
+    Assoc,
+    Expression,
+};
+pub use diagnostic::{
+    Diagnostic,
+    Label,
+    Report,
+    Severity,
+};
+pub use expected::{
+    Expected,
+};
+pub use indent::{
+    BadIndent,
+};
+pub use lexer::{
+    Lexer,
+    NoToken,
+    Token,
+};
+pub use binary::{
+    BinaryFailure,
+};
+pub use driver::{
+    Driver,
+};
+pub use recursive::{
+    ForwardDecl,
+};
+pub use grammar::{
+    Grammar,
+    RuleName,
+};
+pub use profile::{
+    Profile,
+    RuleStats,
+};
+pub use trace::{
+    Outcome,
+    Trace,
+    TraceNode,
+};
 pub use combine_fail::{
     CombineFail,
     CombineManyFail,
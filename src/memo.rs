@@ -0,0 +1,214 @@
+use crate::grammar::{Grammar, RuleName};
+use crate::parser::{ParseResult, Parser};
+use crate::state::State;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// The results of a memoized parser in one run of a session (the session's
+/// id, and `Session::run`).
+struct Memo<T, E, F> {
+    run: Option<(u64, u64)>,
+    results: HashMap<Key, ParseResult<T, E, F>>,
+}
+
+/// Where a result was parsed. A run can parse parts of its input on their
+/// own (like `length_prefixed` does), so the text is part of the key, told
+/// apart by its address and size.
+#[derive(PartialEq, Eq, Hash)]
+struct Key {
+    text: *const (),
+    size: usize,
+    partial: bool,
+    offset: usize,
+}
+
+impl Key {
+    fn of<I: ?Sized>(state: &State<I>) -> Self {
+        Key {
+            text: state.text as *const I as *const (),
+            size: std::mem::size_of_val(state.text),
+            partial: state.partial,
+            offset: state.pos.offset,
+        }
+    }
+}
+
+impl<'a, T, E, F, I> Parser<'a, T, E, F, I>
+where
+    T: Clone + 'a,
+    E: Clone + 'a,
+    F: Clone + 'a,
+    I: ?Sized,
+{
+    /// Remembers the result of this parser at every offset, so running it
+    /// again at the same place (like after `or` backtracks) is free. This is
+    /// packrat parsing: memoizing the parsers that alternatives share makes
+    /// backtracking grammars run in linear time.
+    ///
+    /// The results are kept for the current run of the session, and are
+    /// forgotten when the next run starts (a call to `parse` that is not
+    /// inside another one). Without a session, nothing is remembered.
+    /// `Incomplete` results are not remembered, since more input can change
+    /// them.
+    ///
+    /// Clones of the returned parser share the table, so build it once and
+    /// clone it; calling a function that makes the parser again makes a new
+    /// table. The result at an offset must only depend on the offset, so
+    /// don't memoize parsers that read a context or that recurse to the left
+    /// (use `Parser::left_recursive` for those). Results of runs that
+    /// recorded something in the session (like a recovered error) are not
+    /// remembered, because a remembered result can't record it again.
+    ///
+    /// For outputs that are not `Clone`, see `memoize_rc`.
+    pub fn memoize(self) -> Self {
+        let name = format!("memoize({})", self.name);
+        let grammar = self.grammar.clone();
+        let memo = RefCell::new(Memo {
+            run: None,
+            results: HashMap::new(),
+        });
+        Parser::from_fn(move |state| {
+            let Some(session) = state.session else {
+                return self.parse(state);
+            };
+            let key = Key::of(&state);
+            {
+                let mut memo = memo.borrow_mut();
+                let run = Some((session.id(), session.run()));
+                if memo.run != run {
+                    memo.run = run;
+                    memo.results.clear();
+                }
+                if let Some(result) = memo.results.get(&key) {
+                    return result.clone();
+                }
+            }
+            let checkpoint = session.checkpoint();
+            let result = self.parse(state);
+            let incomplete = matches!(result, ParseResult::Incomplete(_));
+            if !incomplete && session.checkpoint() == checkpoint {
+                memo.borrow_mut().results.insert(key, result.clone());
+            }
+            result
+        })
+        .with_name(name)
+        .described(grammar)
+    }
+}
+
+impl<'a, T, E, F, I> Parser<'a, T, E, F, I>
+where
+    T: 'a,
+    E: Clone + 'a,
+    F: Clone + 'a,
+    I: ?Sized,
+{
+    /// Like `memoize`, for outputs that are not `Clone` (or are expensive to
+    /// clone). The output is shared between the runs in an `Rc`.
+    pub fn memoize_rc(self) -> Parser<'a, Rc<T>, E, F, I> {
+        let name = format!("memoize_rc({})", self.name);
+        self.map(Rc::new).memoize().with_name(name)
+    }
+}
+
+impl<'a, T, E, F, I> Parser<'a, T, E, F, I>
+where
+    T: Clone + 'a,
+    E: Clone + 'a,
+    F: Clone + Default + 'a,
+    I: ?Sized + 'a,
+{
+    /// Makes a parser that can use itself on the left, like
+    /// `expr = expr '+' term | term`. `f` gets the parser itself (`this`) and
+    /// returns its definition.
+    ///
+    /// This grows a seed (Warth et al.): the first time `this` is reached at
+    /// an offset it fails, so only the alternatives that don't start with it
+    /// can match. Then the definition runs again with `this` returning the
+    /// last match, as long as the match gets longer. The matches that are
+    /// still growing are kept in a table by offset.
+    ///
+    /// Only direct left recursion is supported: `this` has to be reached
+    /// from the definition itself, and not through another left recursive
+    /// parser.
+    ///
+    /// # Panics
+    /// When `this` is used after the returned parser was dropped.
+    pub fn left_recursive(f: impl FnOnce(Self) -> Self) -> Self {
+        let definition = Rc::new(RefCell::new(None::<Self>));
+        let growing = Rc::new(RefCell::new(HashMap::<usize, ParseResult<T, E, F>>::new()));
+        let rule = RuleName::new("left_recursive");
+        let this = {
+            let definition = Rc::downgrade(&definition);
+            let growing = growing.clone();
+            Parser::from_fn(move |state| {
+                if let Some(seed) = growing.borrow().get(&state.pos.offset) {
+                    return seed.clone();
+                }
+                let definition = definition
+                    .upgrade()
+                    .expect("left_recursive: used after the parser was dropped");
+                let definition = definition.borrow().clone().unwrap();
+                grow(&definition, &growing, state)
+            })
+            .with_grammar(Grammar::Ref(rule.clone()))
+        };
+        let parser = f(this);
+        let name = format!("left_recursive({})", parser.name);
+        let grammar = Grammar::define(rule, parser.describe(), &name);
+        *definition.borrow_mut() = Some(parser);
+        Parser::from_fn(move |state| {
+            // Holding on to the definition keeps `this` alive.
+            let definition = definition.borrow().clone().unwrap();
+            grow(&definition, &growing, state)
+        })
+        .with_name(name)
+        .with_grammar(grammar)
+    }
+}
+
+/// Runs `definition` at `state` over and over, while every run gets further
+/// than the one before it. See `Parser::left_recursive`.
+fn grow<'a, T, E, F, I>(
+    definition: &Parser<'a, T, E, F, I>,
+    growing: &RefCell<HashMap<usize, ParseResult<T, E, F>>>,
+    state: State<'a, I>,
+) -> ParseResult<T, E, F>
+where
+    T: Clone,
+    E: Clone,
+    F: Clone + Default,
+    I: ?Sized,
+{
+    let offset = state.pos.offset;
+    let mut best = ParseResult::Fail(F::default(), state.pos);
+    growing.borrow_mut().insert(offset, best.clone());
+    loop {
+        let checkpoint = state.checkpoint();
+        let result = definition.parse(state);
+        match (&result, &best) {
+            // It didn't get further, so the last match is the longest.
+            (ParseResult::Ok(_, pos), ParseResult::Ok(_, best_pos))
+                if pos.offset <= best_pos.offset =>
+            {
+                state.rollback(checkpoint);
+                break;
+            }
+            (ParseResult::Ok(..), _) => {
+                best = result;
+                growing.borrow_mut().insert(offset, best.clone());
+            }
+            (ParseResult::Fail(..), ParseResult::Ok(..)) => break,
+            // Failing on the first run, errors and needing more input are
+            // the result.
+            _ => {
+                best = result;
+                break;
+            }
+        }
+    }
+    growing.borrow_mut().remove(&offset);
+    best
+}
@@ -1,5 +1,9 @@
-use crate::position::Pos;
+use crate::grammar::Grammar;
+use crate::input::Input;
+use crate::position::{Pos, Span};
+use crate::session::Session;
 use crate::state::State;
+use crate::trace::Outcome;
 use crate::{CombineFail, CombineManyFail};
 
 use std::rc::Rc;
@@ -13,21 +17,28 @@ pub enum ParseResult<T, E, F = ()> {
     Fail(F, Pos),
     /// The parser expected it could succeed, but it did not.
     Err(E, Pos),
+    /// The parser got to the end of a partial input (see
+    /// `State::with_partial`), and needs at least this many more items (bytes,
+    /// for text) to know how to go on.
+    Incomplete(usize),
 }
 
 /// A parser is a function from a `State` to a `ParseResult`.
 /// The `T` type is the type of the value produced by the parser, the `E` is the
 /// type of the errors it can produce. `F` is the type of failure - this is like
 /// an error, but made to be used for backtracking. It is optional.
-/// The `'a` lifetime is the lifetime of the parser.
-pub struct Parser<'a, T, E, F = ()> {
+/// The `'a` lifetime is the lifetime of the parser. `I` is the input that the
+/// parser runs over (see `Input`), which is source text by default.
+pub struct Parser<'a, T, E, F = (), I: ?Sized = str> {
     /// This name is useful for debugging.
     pub(crate) name: Rc<String>,
+    /// What this parser parses, if it is known (see `Parser::describe`).
+    pub(crate) grammar: Option<Rc<Grammar>>,
     // Maybe here we might want to use a different lifetime?  --v
-    parse: Rc<dyn Fn(State<'a>) -> ParseResult<T, E, F> + 'a>,
+    parse: Rc<dyn Fn(State<'a, I>) -> ParseResult<T, E, F> + 'a>,
 }
 
-impl<T, E, F> std::fmt::Debug for Parser<'_, T, E, F> {
+impl<T, E, F, I: ?Sized> std::fmt::Debug for Parser<'_, T, E, F, I> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let name = self.name.as_ref();
         write!(f, "Parser(\"{name}\")")
@@ -36,16 +47,17 @@ impl<T, E, F> std::fmt::Debug for Parser<'_, T, E, F> {
 
 // Need an explicit clone implementation because the #[derive(Clone)] adds
 // constraints `T: Clone` and such that are unneeded!
-impl<'a, T, E, F> Clone for Parser<'a, T, E, F> {
+impl<'a, T, E, F, I: ?Sized> Clone for Parser<'a, T, E, F, I> {
     fn clone(&self) -> Self {
         Self {
             name: self.name.clone(),
+            grammar: self.grammar.clone(),
             parse: self.parse.clone(),
         }
     }
 }
 
-impl<'a, T, E, F> Parser<'a, T, E, F> {
+impl<'a, T, E, F, I: ?Sized> Parser<'a, T, E, F, I> {
     // Here `T` is bound by `'a` because we are storing a `T` in the returned
     // parser.
     pub fn ret(value: T) -> Self
@@ -54,6 +66,7 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
     {
         Parser {
             name: Rc::new("ret".to_string()),
+            grammar: Some(Rc::new(Grammar::Seq(vec![]))),
             parse: Rc::new(move |state| ParseResult::Ok(value.clone(), state.pos)),
         }
     }
@@ -61,6 +74,7 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
     pub fn ret_with(value: impl Fn() -> T + 'a) -> Self {
         Parser {
             name: Rc::new("ret_with".to_string()),
+            grammar: Some(Rc::new(Grammar::Seq(vec![]))),
             parse: Rc::new(move |state| ParseResult::Ok(value(), state.pos)),
         }
     }
@@ -71,6 +85,7 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
     {
         Parser {
             name: Rc::new("fail".to_string()),
+            grammar: None,
             parse: Rc::new(move |state| ParseResult::Fail(value.clone(), state.pos)),
         }
     }
@@ -78,6 +93,7 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
     pub fn fail_with(value: impl Fn() -> F + 'a) -> Self {
         Parser {
             name: Rc::new("fail_with".to_string()),
+            grammar: None,
             parse: Rc::new(move |state| ParseResult::Fail(value(), state.pos)),
         }
     }
@@ -88,6 +104,7 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
     {
         Parser {
             name: Rc::new("err".to_string()),
+            grammar: None,
             parse: Rc::new(move |state| ParseResult::Err(value.clone(), state.pos)),
         }
     }
@@ -95,22 +112,71 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
     pub fn err_with(value: impl Fn() -> E + 'a) -> Self {
         Parser {
             name: Rc::new("err_with".to_string()),
+            grammar: None,
             parse: Rc::new(move |state| ParseResult::Err(value(), state.pos)),
         }
     }
 
     pub fn from_fn<Func>(func: Func) -> Self
     where
-        Func: Fn(State<'a>) -> ParseResult<T, E, F> + 'a,
+        Func: Fn(State<'a, I>) -> ParseResult<T, E, F> + 'a,
     {
         Parser {
             name: Rc::new("from_fn".to_string()),
+            grammar: None,
             parse: Rc::new(func),
         }
     }
 
-    pub fn parse(&self, state: State<'a>) -> ParseResult<T, E, F> {
-        (self.parse)(state)
+    /// Runs the parser. If it fails (or it needs more input), anything it
+    /// did to the session is rolled back.
+    pub fn parse(&self, state: State<'a, I>) -> ParseResult<T, E, F> {
+        let _running = state.session.map(Session::enter);
+        let checkpoint = state.checkpoint();
+        let tracing = state.session.filter(|session| session.tracer().is_on());
+        let profiling = state.session.filter(|session| session.profiler().is_on());
+        if let Some(session) = tracing {
+            session.tracer().enter(self.name.clone(), state.pos);
+        }
+        if let Some(session) = profiling {
+            session.profiler().enter(&self.name);
+        }
+        let result = (self.parse)(state);
+        if let ParseResult::Fail(..) | ParseResult::Incomplete(_) = result {
+            state.rollback(checkpoint);
+        }
+        if let Some(session) = profiling {
+            session.profiler().exit(&self.name, state.pos, &result);
+        }
+        if let Some(session) = tracing {
+            let (outcome, end) = Outcome::of(&result);
+            session.tracer().exit(outcome, end);
+        }
+        result
+    }
+
+    /// Traces this parser, and every parser it uses (see `Trace`). The trace
+    /// is kept in the session, and `Session::take_trace` returns it. Without
+    /// a session, nothing is traced.
+    pub fn traced(self) -> Self
+    where
+        T: 'a,
+        E: 'a,
+        F: 'a,
+    {
+        let name = format!("traced({})", self.name);
+        let grammar = self.grammar.clone();
+        Parser::from_fn(move |state| {
+            let Some(session) = state.session else {
+                return self.parse(state);
+            };
+            session.tracer().turn_on();
+            let result = self.parse(state);
+            session.tracer().turn_off();
+            result
+        })
+        .with_name(name)
+        .described(grammar)
     }
 
     // More advanced constructors and combinators.
@@ -121,54 +187,63 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
         self
     }
 
-    pub fn map<U>(self, f: impl Fn(T) -> U + 'a) -> Parser<'a, U, E, F>
+    pub fn map<U>(self, f: impl Fn(T) -> U + 'a) -> Parser<'a, U, E, F, I>
     where
         F: 'a,
         E: 'a,
         T: 'a,
     {
         let name = format!("map({})", self.name);
+        let grammar = self.grammar.clone();
         Parser::from_fn(move |state| match self.parse(state) {
             ParseResult::Ok(value, pos) => ParseResult::Ok(f(value), pos),
             ParseResult::Fail(fail_value, pos) => ParseResult::Fail(fail_value, pos),
             ParseResult::Err(err_value, pos) => ParseResult::Err(err_value, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
         })
         .with_name(name)
+        .described(grammar)
     }
 
-    pub fn map_fail<G>(self, f: impl Fn(F) -> G + 'a) -> Parser<'a, T, E, G>
+    pub fn map_fail<G>(self, f: impl Fn(F) -> G + 'a) -> Parser<'a, T, E, G, I>
     where
         F: 'a,
         E: 'a,
         T: 'a,
     {
         let name = format!("map_fail({})", self.name);
+        let grammar = self.grammar.clone();
         Parser::from_fn(move |state| match self.parse(state) {
             ParseResult::Ok(value, pos) => ParseResult::Ok(value, pos),
             ParseResult::Fail(fail_value, pos) => ParseResult::Fail(f(fail_value), pos),
             ParseResult::Err(err_value, pos) => ParseResult::Err(err_value, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
         })
         .with_name(name)
+        .described(grammar)
     }
 
-    pub fn map_err<E2>(self, f: impl Fn(E) -> E2 + 'a) -> Parser<'a, T, E2, F>
+    pub fn map_err<E2>(self, f: impl Fn(E) -> E2 + 'a) -> Parser<'a, T, E2, F, I>
     where
         F: 'a,
         E: 'a,
         T: 'a,
     {
         let name = format!("map_err({})", self.name);
+        let grammar = self.grammar.clone();
         Parser::from_fn(move |state| match self.parse(state) {
             ParseResult::Ok(value, pos) => ParseResult::Ok(value, pos),
             ParseResult::Fail(fail_value, pos) => ParseResult::Fail(fail_value, pos),
             ParseResult::Err(err_value, pos) => ParseResult::Err(f(err_value), pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
         })
         .with_name(name)
+        .described(grammar)
     }
 
-    pub fn and_then<U, Func>(self, func: Func) -> Parser<'a, U, E, F>
+    pub fn and_then<U, Func>(self, func: Func) -> Parser<'a, U, E, F, I>
     where
-        Func: Fn(T) -> Parser<'a, U, E, F> + 'a,
+        Func: Fn(T) -> Parser<'a, U, E, F, I> + 'a,
         F: 'a,
         E: 'a,
         T: 'a,
@@ -178,6 +253,7 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
             ParseResult::Ok(value, pos) => func(value).parse(state.with_pos(pos)),
             ParseResult::Fail(fail_value, pos) => ParseResult::Fail(fail_value, pos),
             ParseResult::Err(err_value, pos) => ParseResult::Err(err_value, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
         })
         .with_name(name)
     }
@@ -186,9 +262,9 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
     /// parser fails.
     /// Unlike `and_then`, the next parser is started at the same position as
     /// the first parser (not at the position that it stopped).
-    pub fn and_then_fail<G, Func>(self, func: Func) -> Parser<'a, T, E, G>
+    pub fn and_then_fail<G, Func>(self, func: Func) -> Parser<'a, T, E, G, I>
     where
-        Func: Fn(F) -> Parser<'a, T, E, G> + 'a,
+        Func: Fn(F) -> Parser<'a, T, E, G, I> + 'a,
         F: 'a,
         E: 'a,
         T: 'a,
@@ -198,6 +274,7 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
             ParseResult::Ok(value, pos) => ParseResult::Ok(value, pos),
             ParseResult::Fail(fail_value, _) => func(fail_value).parse(state),
             ParseResult::Err(err_value, pos) => ParseResult::Err(err_value, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
         })
         .with_name(name)
     }
@@ -206,31 +283,39 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
     /// parser returns an error (Notice - this is different from failure).
     /// Unlike `and_then`, the next parser is started at the same position as
     /// the first parser (not at the position that it stopped).
-    pub fn and_then_err<E2, Func>(self, func: Func) -> Parser<'a, T, E2, F>
+    pub fn and_then_err<E2, Func>(self, func: Func) -> Parser<'a, T, E2, F, I>
     where
-        Func: Fn(E) -> Parser<'a, T, E2, F> + 'a,
+        Func: Fn(E) -> Parser<'a, T, E2, F, I> + 'a,
         F: 'a,
         E: 'a,
         T: 'a,
     {
         let name = format!("and_then_err({})", self.name);
-        Parser::from_fn(move |state| match self.parse(state) {
-            ParseResult::Ok(value, pos) => ParseResult::Ok(value, pos),
-            ParseResult::Fail(fail_value, pos) => ParseResult::Fail(fail_value, pos),
-            ParseResult::Err(err_value, _) => func(err_value).parse(state),
+        Parser::from_fn(move |state| {
+            let checkpoint = state.checkpoint();
+            match self.parse(state) {
+                ParseResult::Ok(value, pos) => ParseResult::Ok(value, pos),
+                ParseResult::Fail(fail_value, pos) => ParseResult::Fail(fail_value, pos),
+                ParseResult::Err(err_value, _) => {
+                    state.rollback(checkpoint);
+                    func(err_value).parse(state)
+                }
+                ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+            }
         })
         .with_name(name)
     }
 
-    pub fn or<G, H>(self, other: Parser<'a, T, E, G>) -> Parser<'a, T, E, H>
+    pub fn or<G, H>(self, other: Parser<'a, T, E, G, I>) -> Parser<'a, T, E, H, I>
     where
         T: 'a,
         E: 'a,
         F: 'a,
         G: 'a,
-        F: CombineFail<'a, G, H>,
+        F: CombineFail<'a, G, H, I>,
     {
         let name = format!("or({} | {})", self.name, other.name);
+        let grammar = Grammar::alt([self.describe(), other.describe()]);
         Parser::from_fn(move |state| match self.parse(state) {
             ParseResult::Ok(value, pos) => ParseResult::Ok(value, pos),
             ParseResult::Fail(f1, f1_pos) => {
@@ -243,14 +328,17 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
                         ParseResult::Fail(f, state.pos)
                     }
                     ParseResult::Err(err_value, pos) => ParseResult::Err(err_value, pos),
+                    ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
                 }
             }
             ParseResult::Err(err_value, pos) => ParseResult::Err(err_value, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
         })
         .with_name(name)
+        .with_grammar(grammar)
     }
 
-    pub fn or_ret<G>(self, x: T) -> Parser<'a, T, E, G>
+    pub fn or_ret<G>(self, x: T) -> Parser<'a, T, E, G, I>
     where
         T: Clone + 'a,
         E: 'a,
@@ -260,7 +348,7 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
         self.or(Parser::ret(x)).map_fail(|(_, f)| f)
     }
 
-    pub fn or_fail<G>(self, f: G) -> Parser<'a, T, E, G>
+    pub fn or_fail<G>(self, f: G) -> Parser<'a, T, E, G, I>
     where
         T: 'a,
         E: 'a,
@@ -270,29 +358,110 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
         self.map_fail(move |_| f.clone())
     }
 
-    pub fn or_err<G>(self, e: E) -> Parser<'a, T, E, G>
+    pub fn or_err<G>(self, e: E) -> Parser<'a, T, E, G, I>
     where
         T: 'a,
         E: Clone + 'a,
         F: 'a,
         G: 'a,
     {
-        self.or(Parser::err(e)).map_fail(|(_f, g)| g)
+        let grammar = self.grammar.clone();
+        self.or(Parser::err(e))
+            .map_fail(|(_f, g)| g)
+            .described(grammar)
+    }
+
+    /// Turns a failure into an error (with `From`), so there is no
+    /// backtracking from here. The error is at the position of the failure,
+    /// which is usually much more helpful than the position where some `or`
+    /// gave up.
+    pub fn cut<G>(self) -> Parser<'a, T, E, G, I>
+    where
+        T: 'a,
+        E: From<F> + 'a,
+        F: 'a,
+        G: 'a,
+    {
+        let name = format!("cut({})", self.name);
+        self.cut_with(E::from).with_name(name)
+    }
+
+    /// Like `cut`, but the failure is turned into an error with `f`.
+    pub fn cut_with<G>(self, f: impl Fn(F) -> E + 'a) -> Parser<'a, T, E, G, I>
+    where
+        T: 'a,
+        E: 'a,
+        F: 'a,
+        G: 'a,
+    {
+        let name = format!("cut_with({})", self.name);
+        let grammar = self.grammar.clone();
+        Parser::from_fn(move |state| match self.parse(state) {
+            ParseResult::Ok(value, pos) => ParseResult::Ok(value, pos),
+            ParseResult::Fail(fail_value, pos) => ParseResult::Err(f(fail_value), pos),
+            ParseResult::Err(err_value, pos) => ParseResult::Err(err_value, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+        })
+        .with_name(name)
+        .described(grammar)
+    }
+
+    /// Like `and_then`, but commits once this parser succeeds: if the next
+    /// parser fails, it is an error (see `cut`). For example, after the
+    /// `if` keyword, a missing condition is an error, and not a reason to
+    /// try the other kinds of statements.
+    pub fn then_commit<U, G, Func>(self, func: Func) -> Parser<'a, U, E, F, I>
+    where
+        Func: Fn(T) -> Parser<'a, U, E, G, I> + 'a,
+        T: 'a,
+        U: 'a,
+        E: From<G> + 'a,
+        F: 'a,
+        G: 'a,
+    {
+        let name = format!("then_commit({})", self.name);
+        self.and_then(move |value| func(value).cut())
+            .with_name(name)
+    }
+
+    /// Like `then_commit`, but the failure is turned into an error with `f`.
+    pub fn then_commit_with<U, G, Func>(
+        self,
+        func: Func,
+        f: impl Fn(G) -> E + 'a,
+    ) -> Parser<'a, U, E, F, I>
+    where
+        Func: Fn(T) -> Parser<'a, U, E, G, I> + 'a,
+        T: 'a,
+        U: 'a,
+        E: 'a,
+        F: 'a,
+        G: 'a,
+    {
+        let name = format!("then_commit_with({})", self.name);
+        let f = Rc::new(f);
+        self.and_then(move |value| {
+            let f = f.clone();
+            func(value).cut_with(move |fail| f(fail))
+        })
+        .with_name(name)
     }
 
     pub fn one_of<G>(
-        parsers: impl IntoIterator<Item = Parser<'a, T, E, F>> + 'a,
-    ) -> Parser<'a, T, E, G>
+        parsers: impl IntoIterator<Item = Parser<'a, T, E, F, I>> + 'a,
+    ) -> Parser<'a, T, E, G, I>
     where
         T: 'a,
         E: 'a,
-        F: 'a + CombineManyFail<'a, G>,
+        F: 'a + CombineManyFail<'a, G, I>,
         G: 'a,
     {
         let mut ret = Parser::fail_with(Vec::new);
         let mut names = vec![];
+        let mut options = vec![];
         for parser in parsers {
             names.push(parser.name.clone());
+            options.push(parser.describe());
             ret = ret.or(parser).map_fail(|(f1, _f1_state, f2, f2_state)| {
                 let mut f = f1;
                 f.push((f2, f2_state));
@@ -307,7 +476,9 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
                 .collect::<Vec<_>>()
                 .join(", ")
         );
-        ret.map_fail(F::combine_many_fail).with_name(name)
+        ret.map_fail(F::combine_many_fail)
+            .with_name(name)
+            .with_grammar(Grammar::alt(options))
     }
 
     pub fn filter(self, pred: impl Fn(&T) -> bool + 'a) -> Self
@@ -317,6 +488,7 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
         F: Clone + Default + 'a,
     {
         let name = format!("filter({})", self.name);
+        let grammar = self.grammar.clone();
         Parser::and_then(self, move |value| {
             if pred(&value) {
                 Parser::ret(value)
@@ -325,14 +497,86 @@ impl<'a, T, E, F> Parser<'a, T, E, F> {
             }
         })
         .with_name(name)
+        .described(grammar)
+    }
+}
+
+impl<'a, T, E, F, I: ?Sized> Parser<'a, T, E, F, I> {
+    /// Returns the value of this parser together with the span it parsed.
+    pub fn spanned(self) -> Parser<'a, (T, Span), E, F, I>
+    where
+        T: 'a,
+        E: 'a,
+        F: 'a,
+    {
+        let name = format!("spanned({})", self.name);
+        let grammar = self.grammar.clone();
+        Parser::from_fn(move |state| match self.parse(state) {
+            ParseResult::Ok(value, pos) => ParseResult::Ok((value, Span::new(state.pos, pos)), pos),
+            ParseResult::Fail(fail_value, pos) => ParseResult::Fail(fail_value, pos),
+            ParseResult::Err(err_value, pos) => ParseResult::Err(err_value, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+        })
+        .with_name(name)
+        .described(grammar)
+    }
+
+    /// Like `map`, but the function also gets the span that was parsed.
+    pub fn with_span<U>(self, f: impl Fn(T, Span) -> U + 'a) -> Parser<'a, U, E, F, I>
+    where
+        T: 'a,
+        E: 'a,
+        F: 'a,
+    {
+        let name = format!("with_span({})", self.name);
+        self.spanned()
+            .map(move |(value, span)| f(value, span))
+            .with_name(name)
+    }
+
+    /// Discards the value of this parser, and returns the input it consumed
+    /// instead. This borrows from the source, so nothing is allocated.
+    pub fn recognize(self) -> Parser<'a, I::Slice<'a>, E, F, I>
+    where
+        I: Input,
+        T: 'a,
+        E: 'a,
+        F: 'a,
+    {
+        let name = format!("recognize({})", self.name);
+        self.with_consumed().map(|(_, text)| text).with_name(name)
+    }
+
+    /// Returns the value of this parser together with the input it consumed.
+    pub fn with_consumed(self) -> Parser<'a, (T, I::Slice<'a>), E, F, I>
+    where
+        I: Input,
+        T: 'a,
+        E: 'a,
+        F: 'a,
+    {
+        let name = format!("with_consumed({})", self.name);
+        let grammar = self.grammar.clone();
+        Parser::from_fn(move |state: State<'a, I>| match self.parse(state) {
+            ParseResult::Ok(value, pos) => {
+                let text = state.slice(Span::new(state.pos, pos));
+                ParseResult::Ok((value, text), pos)
+            }
+            ParseResult::Fail(fail_value, pos) => ParseResult::Fail(fail_value, pos),
+            ParseResult::Err(err_value, pos) => ParseResult::Err(err_value, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+        })
+        .with_name(name)
+        .described(grammar)
     }
 }
 
-impl<'a, E, F> Parser<'a, State<'a>, E, F> {
+impl<'a, E, F, I: ?Sized> Parser<'a, State<'a, I>, E, F, I> {
     /// A parser that just returns the current state.
     pub fn state() -> Self {
         Parser {
             name: Rc::new("state".to_string()),
+            grammar: None,
             parse: Rc::new(|state| ParseResult::Ok(state, state.pos)),
         }
     }
@@ -348,3 +592,47 @@ macro_rules! one_of {
         )
     }};
 }
+
+/// Sequences parsers, like do notation:
+/// `parse! { let a = p1; let _ = p2; let b = p3; ret (a, b) }`.
+///
+/// Every `let` runs a parser and binds its value, and the parsers after it
+/// can use the value. A parser can also run on its own, like `p2;`. The last
+/// line is `ret value`, or a parser whose value is returned. The first
+/// failure or error is returned, like with `and_then`, which this expands to.
+///
+/// The values that are bound are cloned for every run of the parsers after
+/// them, so they must be `Clone`.
+#[macro_export]
+macro_rules! parse {
+    (@bound [$($bound:ident)*] ret $value:expr $(;)?) => {
+        $crate::Parser::ret($value)
+    };
+    (@bound [$($bound:ident)*] let _ = $parser:expr; $($rest:tt)+) => {
+        $crate::Parser::and_then($parser, move |_| {
+            $(
+                #[allow(unused_variables)]
+                let $bound = ::std::clone::Clone::clone(&$bound);
+            )*
+            $crate::parse!(@bound [$($bound)*] $($rest)+)
+        })
+    };
+    (@bound [$($bound:ident)*] let $name:ident = $parser:expr; $($rest:tt)+) => {
+        $crate::Parser::and_then($parser, move |$name| {
+            $(
+                #[allow(unused_variables)]
+                let $bound = ::std::clone::Clone::clone(&$bound);
+            )*
+            $crate::parse!(@bound [$($bound)* $name] $($rest)+)
+        })
+    };
+    (@bound [$($bound:ident)*] $parser:expr; $($rest:tt)+) => {
+        $crate::parse!(@bound [$($bound)*] let _ = $parser; $($rest)+)
+    };
+    (@bound [$($bound:ident)*] $parser:expr $(;)?) => {
+        $parser
+    };
+    ($($body:tt)+) => {
+        $crate::parse!(@bound [] $($body)+)
+    };
+}
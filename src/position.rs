@@ -4,6 +4,8 @@ pub type Col = u16;
 /// A position in some source code string.
 /// Positions save the offset, the row and the column. That means that a
 /// position is only valid for a specific source code string.
+/// Rows and columns stop growing at their maximum (`u16::MAX`), so on very
+/// long lines or files they are not exact, but the offset always is.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Pos {
     /// Starts at 0.
@@ -24,12 +26,14 @@ impl Pos {
         }
     }
 
-    /// Returns this position, one character to the right.
+    /// Returns this position, one byte to the right.
+    /// This is only correct for single-byte (ASCII) characters - prefer
+    /// `Pos::advance` when the character is not known in advance.
     pub const fn right(self) -> Self {
         Pos {
             offset: self.offset + 1,
             row: self.row,
-            col: self.col + 1,
+            col: self.col.saturating_add(1),
         }
     }
 
@@ -37,10 +41,104 @@ impl Pos {
     pub const fn down(self) -> Self {
         Pos {
             offset: self.offset + 1,
-            row: self.row + 1,
+            row: self.row.saturating_add(1),
             col: 1,
         }
     }
+
+    /// Returns this position after the character `ch`.
+    /// The offset moves by the UTF-8 length of `ch`, and the column by one.
+    pub const fn advance(self, ch: char) -> Self {
+        if ch == '\n' {
+            return self.down();
+        }
+        Pos {
+            offset: self.offset + ch.len_utf8(),
+            row: self.row,
+            col: self.col.saturating_add(1),
+        }
+    }
+
+    /// Returns this position after the character `ch`, without moving the
+    /// column. This is used for characters that continue the previous
+    /// grapheme cluster (like combining accents).
+    pub const fn extend(self, ch: char) -> Self {
+        Pos {
+            offset: self.offset + ch.len_utf8(),
+            ..self
+        }
+    }
+}
+
+/// A range of source code, from `start` (inclusive) to `end` (exclusive).
+/// Like `Pos`, a span is only valid for a specific source code string.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+impl Span {
+    pub const fn new(start: Pos, end: Pos) -> Self {
+        Span { start, end }
+    }
+
+    /// An empty span at `pos`.
+    pub const fn at(pos: Pos) -> Self {
+        Span::new(pos, pos)
+    }
+
+    /// The length of the span in bytes.
+    pub const fn len(&self) -> usize {
+        self.end.offset - self.start.offset
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the smallest span that covers both spans.
+    pub const fn to(self, other: Span) -> Span {
+        let start = if other.start.offset < self.start.offset {
+            other.start
+        } else {
+            self.start
+        };
+        let end = if other.end.offset > self.end.offset {
+            other.end
+        } else {
+            self.end
+        };
+        Span { start, end }
+    }
+
+    pub const fn contains(&self, pos: Pos) -> bool {
+        self.start.offset <= pos.offset && pos.offset < self.end.offset
+    }
+}
+
+impl From<Pos> for Span {
+    fn from(pos: Pos) -> Self {
+        Span::at(pos)
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let Span { start, end } = *self;
+        write!(f, "{}:{}-{}:{}", start.row, start.col, end.row, end.col)
+    }
+}
+
+/// What a single column stands for.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnMode {
+    /// Every `char` is one column.
+    #[default]
+    Chars,
+    /// Every extended grapheme cluster is one column. This is closer to what
+    /// a user sees in their editor, but a bit slower.
+    Graphemes,
 }
 
 impl Default for Pos {
@@ -0,0 +1,212 @@
+use crate::parser::{ParseResult, Parser};
+use crate::state::State;
+
+use std::rc::Rc;
+
+/// How an infix operator groups with other operators of the same precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Assoc {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`.
+    Right,
+    /// `a == b == c` is not allowed. The expression stops after `a == b`,
+    /// and the rest is left for the next parser.
+    None,
+}
+
+type Unary<'a, T> = Box<dyn FnOnce(T) -> T + 'a>;
+type Binary<'a, T> = Box<dyn FnOnce(T, T) -> T + 'a>;
+
+struct Operator<'a, Fold, E, I: ?Sized> {
+    /// Parses the operator, and returns how to apply it.
+    op: Parser<'a, Fold, E, (), I>,
+    precedence: u16,
+    assoc: Assoc,
+}
+
+/// A builder for an operator-precedence (Pratt) expression parser.
+/// Start one with `Parser::expression`, add operators, and call `build`.
+/// Operators with a higher precedence bind tighter.
+pub struct Expression<'a, T, E, F, I: ?Sized = str> {
+    atom: Parser<'a, T, E, F, I>,
+    prefix: Vec<Operator<'a, Unary<'a, T>, E, I>>,
+    infix: Vec<Operator<'a, Binary<'a, T>, E, I>>,
+    postfix: Vec<Operator<'a, Unary<'a, T>, E, I>>,
+}
+
+impl<'a, T, E, F, I: ?Sized> Parser<'a, T, E, F, I> {
+    /// Starts building an expression parser, where this parser parses the
+    /// operands (numbers, variables, parenthesized expressions, ...).
+    pub fn expression(self) -> Expression<'a, T, E, F, I> {
+        Expression {
+            atom: self,
+            prefix: vec![],
+            infix: vec![],
+            postfix: vec![],
+        }
+    }
+}
+
+impl<'a, T: 'a, E: 'a, F: 'a, I: ?Sized> Expression<'a, T, E, F, I> {
+    /// Adds a prefix operator, like `-x`.
+    pub fn prefix<O: 'a, G: 'a>(
+        mut self,
+        op: Parser<'a, O, E, G, I>,
+        precedence: u16,
+        fold: impl Fn(O, T) -> T + 'a,
+    ) -> Self {
+        let fold = Rc::new(fold);
+        let op = op.map_fail(|_| ()).map(move |o| -> Unary<T> {
+            let fold = fold.clone();
+            Box::new(move |x| fold(o, x))
+        });
+        let assoc = Assoc::Left;
+        self.prefix.push(Operator {
+            op,
+            precedence,
+            assoc,
+        });
+        self
+    }
+
+    /// Adds an infix operator, like `x + y`.
+    pub fn infix<O: 'a, G: 'a>(
+        mut self,
+        op: Parser<'a, O, E, G, I>,
+        precedence: u16,
+        assoc: Assoc,
+        fold: impl Fn(T, O, T) -> T + 'a,
+    ) -> Self {
+        let fold = Rc::new(fold);
+        let op = op.map_fail(|_| ()).map(move |o| -> Binary<T> {
+            let fold = fold.clone();
+            Box::new(move |x, y| fold(x, o, y))
+        });
+        self.infix.push(Operator {
+            op,
+            precedence,
+            assoc,
+        });
+        self
+    }
+
+    /// Adds a postfix operator, like `x!`.
+    pub fn postfix<O: 'a, G: 'a>(
+        mut self,
+        op: Parser<'a, O, E, G, I>,
+        precedence: u16,
+        fold: impl Fn(T, O) -> T + 'a,
+    ) -> Self {
+        let fold = Rc::new(fold);
+        let op = op.map_fail(|_| ()).map(move |o| -> Unary<T> {
+            let fold = fold.clone();
+            Box::new(move |x| fold(x, o))
+        });
+        let assoc = Assoc::Left;
+        self.postfix.push(Operator {
+            op,
+            precedence,
+            assoc,
+        });
+        self
+    }
+
+    /// Builds the expression parser. It fails only when there is no operand
+    /// to start with. An operator with no operand after it is not consumed.
+    pub fn build(self) -> Parser<'a, T, E, F, I> {
+        let name = format!("expression({})", self.atom.name);
+        let table = Rc::new(self);
+        Parser::from_fn(move |state| table.parse_bp(state, 0)).with_name(name)
+    }
+
+    /// Parses an expression whose operators all bind at least as tight as
+    /// `min_bp`. Binding powers are doubled precedences, where the odd
+    /// numbers break the ties for associativity.
+    fn parse_bp(&self, state: State<'a, I>, min_bp: u32) -> ParseResult<T, E, F> {
+        let (mut lhs, mut state) = match self.parse_prefix(state) {
+            ParseResult::Ok(lhs, pos) => (lhs, state.with_pos(pos)),
+            ParseResult::Fail(f, pos) => return ParseResult::Fail(f, pos),
+            ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+            ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+        };
+        // The precedence of the last non-associative operator, which can't be
+        // followed by another operator of the same precedence.
+        let mut non_assoc = None;
+        'operators: loop {
+            for op in &self.postfix {
+                if 2 * u32::from(op.precedence) < min_bp {
+                    continue;
+                }
+                match op.op.parse(state) {
+                    ParseResult::Ok(fold, pos) => {
+                        lhs = fold(lhs);
+                        state = state.with_pos(pos);
+                        continue 'operators;
+                    }
+                    ParseResult::Fail((), _) => {}
+                    ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                    ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+                }
+            }
+            for op in &self.infix {
+                let (l_bp, r_bp) = binding_power(op.precedence, op.assoc);
+                if l_bp < min_bp || non_assoc == Some(op.precedence) {
+                    continue;
+                }
+                let checkpoint = state.checkpoint();
+                let (fold, after_op) = match op.op.parse(state) {
+                    ParseResult::Ok(fold, pos) => (fold, pos),
+                    ParseResult::Fail((), _) => continue,
+                    ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                    ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+                };
+                match self.parse_bp(state.with_pos(after_op), r_bp) {
+                    ParseResult::Ok(rhs, pos) => {
+                        lhs = fold(lhs, rhs);
+                        state = state.with_pos(pos);
+                        non_assoc = (op.assoc == Assoc::None).then_some(op.precedence);
+                        continue 'operators;
+                    }
+                    // An operator without an operand after it is not consumed.
+                    ParseResult::Fail(..) => state.rollback(checkpoint),
+                    ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                    ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+                }
+            }
+            break;
+        }
+        ParseResult::Ok(lhs, state.pos)
+    }
+
+    /// Parses an operand, with all of the prefix operators before it.
+    fn parse_prefix(&self, state: State<'a, I>) -> ParseResult<T, E, F> {
+        for op in &self.prefix {
+            let checkpoint = state.checkpoint();
+            let (fold, after_op) = match op.op.parse(state) {
+                ParseResult::Ok(fold, pos) => (fold, pos),
+                ParseResult::Fail((), _) => continue,
+                ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+            };
+            let (_, r_bp) = binding_power(op.precedence, op.assoc);
+            match self.parse_bp(state.with_pos(after_op), r_bp) {
+                ParseResult::Ok(x, pos) => return ParseResult::Ok(fold(x), pos),
+                // Maybe this is not an operator, but the start of an operand.
+                ParseResult::Fail(..) => state.rollback(checkpoint),
+                ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+            }
+        }
+        self.atom.parse(state)
+    }
+}
+
+/// Returns the left and right binding powers of an operator.
+const fn binding_power(precedence: u16, assoc: Assoc) -> (u32, u32) {
+    let bp = 2 * precedence as u32;
+    match assoc {
+        Assoc::Left | Assoc::None => (bp, bp + 1),
+        Assoc::Right => (bp + 1, bp),
+    }
+}
@@ -1,10 +1,14 @@
+use crate::grammar::Grammar;
+use crate::input::Input;
 use crate::parser::{ParseResult, Parser};
+use crate::position::Pos;
 use crate::state::State;
+use crate::CombineFail;
 
-use functionality::prelude::*;
+use std::ops::{Bound, RangeBounds};
 
-impl<'a, T: 'a, F: 'a, E: 'a> Parser<'a, T, E, F> {
-    pub fn of_bool(value: bool) -> Parser<'a, T, E, F>
+impl<'a, T: 'a, F: 'a, E: 'a, I: ?Sized> Parser<'a, T, E, F, I> {
+    pub fn of_bool(value: bool) -> Parser<'a, T, E, F, I>
     where
         T: Default + Clone,
         F: Default + Clone,
@@ -22,19 +26,11 @@ pub struct EofFailure;
 
 impl<'a, E: 'a> Parser<'a, char, E, EofFailure> {
     pub fn char() -> Parser<'a, char, E, EofFailure> {
-        Parser::from_fn(|state| {
-            if state.rest().is_empty() {
-                ParseResult::Fail(EofFailure, state.pos)
-            } else {
-                let ch = state.rest().chars().next().unwrap();
-                let new_pos = if ch == '\n' {
-                    state.pos.down()
-                } else {
-                    state.pos.right()
-                };
-                ParseResult::Ok(ch, new_pos)
-            }
+        Parser::from_fn(|state| match state.next_char() {
+            Some((ch, new_pos)) => ParseResult::Ok(ch, new_pos),
+            None => state.out_of_input(1, EofFailure),
         })
+        .with_grammar(Grammar::Terminal("char".to_string()))
     }
 }
 
@@ -43,6 +39,40 @@ impl<'a, F: Clone + Default + 'a, E: 'a> Parser<'a, char, E, F> {
         Parser::char()
             .map_fail(|_| F::default())
             .filter(move |&c| c == ch)
+            .with_grammar(Grammar::Literal(ch.to_string()))
+    }
+}
+
+impl<'a, E: 'a, I: ?Sized + Input> Parser<'a, I::Item<'a>, E, EofFailure, I> {
+    /// Parses any single item of the input. For text, this is `char`.
+    pub fn item() -> Self {
+        Parser::from_fn(|state: State<'a, I>| match state.next_item() {
+            Some((item, new_pos)) => ParseResult::Ok(item, new_pos),
+            None => state.out_of_input(1, EofFailure),
+        })
+        .with_name("item")
+    }
+}
+
+impl<'a, E: 'a, F: Default + 'a, I: ?Sized + Input> Parser<'a, I::Item<'a>, E, F, I> {
+    /// Parses a single item that `pred` accepts. Unlike `filter`, this fails
+    /// at the start of the item (and not after it).
+    pub fn satisfy(pred: impl Fn(&I::Item<'a>) -> bool + 'a) -> Self {
+        Parser::from_fn(move |state: State<'a, I>| match state.next_item() {
+            Some((item, new_pos)) if pred(&item) => ParseResult::Ok(item, new_pos),
+            Some(_) => ParseResult::Fail(F::default(), state.pos),
+            None => state.out_of_input(1, F::default()),
+        })
+        .with_name("satisfy")
+    }
+
+    /// Parses a single item that is equal to `expected`.
+    pub fn item_eq(expected: I::Item<'a>) -> Self
+    where
+        I::Item<'a>: PartialEq + std::fmt::Debug,
+    {
+        let name = format!("item_eq({expected:?})");
+        Parser::satisfy(move |item| *item == expected).with_name(name)
     }
 }
 
@@ -54,6 +84,7 @@ impl<'a, E: 'a> Parser<'a, char, E, NotWhitespace> {
         Parser::char()
             .map_fail(|_| NotWhitespace)
             .filter(|c| c.is_whitespace())
+            .with_grammar(Grammar::Terminal("whitespace".to_string()))
     }
 }
 
@@ -71,6 +102,7 @@ impl<'a, E: 'a> Parser<'a, char, E, NotALetter> {
         Parser::char()
             .map_fail(|_| NotALetter)
             .filter(|c| c.is_ascii_alphabetic())
+            .with_grammar(Grammar::Terminal("letter".to_string()))
     }
 }
 
@@ -82,93 +114,289 @@ impl<'a, E: 'a> Parser<'a, char, E, NotADigit> {
         Parser::char()
             .map_fail(|_| NotADigit)
             .filter(|c| c.is_ascii_digit())
+            .with_grammar(Grammar::Terminal("digit".to_string()))
     }
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NotFound;
 
-impl<'a, E: 'a> Parser<'a, char, E, NotFound> {
-    pub fn eof() -> Parser<'a, (), E, NotFound> {
-        Parser::state()
-            .map(|state| state.rest().is_empty())
-            .and_then(Parser::of_bool)
+impl<'a, E: 'a, I: ?Sized + Input> Parser<'a, (), E, NotFound, I> {
+    /// Succeeds at the end of the input. On a partial input, that can't be
+    /// known yet, so it asks for more.
+    pub fn eof() -> Parser<'a, (), E, NotFound, I> {
+        Parser::from_fn(|state: State<'a, I>| match state.eof() {
+            true if state.partial => ParseResult::Incomplete(1),
+            true => ParseResult::Ok((), state.pos),
+            false => ParseResult::Fail(NotFound, state.pos),
+        })
+        .with_name("eof")
     }
+}
 
+impl<'a, E: 'a> Parser<'a, char, E, NotFound> {
     pub fn expect_string(expected: &'static str) -> Parser<'a, (), E, NotFound> {
-        Parser::state()
-            .map(move |state: State| state.rest().starts_with(expected))
-            .and_then(Parser::of_bool)
-            .and_then(move |()| {
-                Parser::from_fn(move |state| {
-                    // TODO: This could be so much cleaner. Also, need to handle newlines?
-                    assert!(expected.chars().all(|c| c != '\n'));
-                    let mut new_pos = state.pos;
-                    for _ in 0..expected.len() {
-                        new_pos = new_pos.right();
-                    }
-                    ParseResult::Ok((), new_pos)
-                })
-            })
+        Parser::from_fn(move |state: State<'a>| {
+            let rest = state.rest();
+            if rest.starts_with(expected) {
+                ParseResult::Ok((), state.skip(expected.len()))
+            } else if expected.starts_with(rest) {
+                state.out_of_input(expected.len() - rest.len(), NotFound)
+            } else {
+                ParseResult::Fail(NotFound, state.pos)
+            }
+        })
+        .with_grammar(Grammar::Literal(expected.to_string()))
     }
 }
 
-impl<'a, T, F, E> Parser<'a, T, E, F> {
+impl<'a, T, F, E, I: ?Sized> Parser<'a, T, E, F, I> {
     /// Repeats this parser zero or one times.
-    pub fn maybe<G>(self) -> Parser<'a, Option<T>, E, G>
+    pub fn maybe<G>(self) -> Parser<'a, Option<T>, E, G, I>
     where
         T: 'a,
         E: 'a,
         F: 'a,
     {
         let name = format!("maybe({})", &self.name);
+        let grammar = self.repeat_grammar(0, Some(1));
         self.map(Some)
-            .or(Parser::<_, _, ()>::ret_with(|| None))
+            .or(Parser::<_, _, (), I>::ret_with(|| None))
             .map_fail(|()| panic!("maybe should not fail"))
             .with_name(name)
+            .with_grammar(grammar)
     }
 
     /// Repeats this parser zero or more times.
-    pub fn repeat_0<G>(self) -> Parser<'a, Vec<T>, E, G>
+    /// This stops at the first failure, or when the parser succeeds without
+    /// consuming any input (otherwise, it would loop forever). A success that
+    /// does not consume input is still included in the result.
+    pub fn repeat_0<G>(self) -> Parser<'a, Vec<T>, E, G, I>
     where
-        T: Clone + 'a,
+        T: 'a,
         E: 'a,
         F: 'a,
         G: 'a,
     {
         let name = format!("repeat_0({})", &self.name);
-        self.clone()
-            .and_then(move |x| {
-                self.clone()
-                    .repeat_0()
-                    .map(move |xs| vec![x.clone()].mutate(|v| v.extend(xs)))
-            })
-            .or(Parser::<_, _, G>::ret_with(Vec::new))
-            .map_fail(|(_, g)| g)
+        let grammar = self.repeat_grammar(0, None);
+        Parser::from_fn(move |state| self.repeat_from(state, Vec::new()))
             .with_name(name)
+            .with_grammar(grammar)
     }
 
     /// Repeats this parser one or more times.
-    pub fn repeat_1(self) -> Parser<'a, Vec<T>, E, F>
+    pub fn repeat_1(self) -> Parser<'a, Vec<T>, E, F, I>
     where
-        T: Clone + 'a,
+        T: 'a,
         E: 'a,
         F: 'a,
     {
         let name = format!("repeat_1({})", &self.name);
-        self.clone()
-            .and_then(move |x| {
-                self.clone()
-                    .repeat_0()
-                    .map(move |xs| vec![x.clone()].mutate(|v| v.extend(xs)))
-            })
-            .with_name(name)
+        let grammar = self.repeat_grammar(1, None);
+        Parser::from_fn(move |state| match self.parse(state) {
+            // Like in `repeat_from`, an item that consumed nothing is the last.
+            ParseResult::Ok(x, pos) if pos == state.pos => ParseResult::Ok(vec![x], pos),
+            ParseResult::Ok(x, pos) => self.repeat_from(state.with_pos(pos), vec![x]),
+            ParseResult::Fail(f, pos) => ParseResult::Fail(f, pos),
+            ParseResult::Err(e, pos) => ParseResult::Err(e, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+        })
+        .with_name(name)
+        .with_grammar(grammar)
+    }
+
+    /// Describes this parser repeated `min` to `max` times.
+    pub(crate) fn repeat_grammar(&self, min: usize, max: Option<usize>) -> Grammar {
+        let inner = Box::new(self.describe());
+        Grammar::Repeat { inner, min, max }
+    }
+
+    /// Keeps parsing from `state`, pushing the results onto `items`, until
+    /// the parser fails or consumes nothing. This is a loop (and not
+    /// recursion) so that long repetitions do not overflow the stack.
+    fn repeat_from<G>(
+        &self,
+        mut state: State<'a, I>,
+        mut items: Vec<T>,
+    ) -> ParseResult<Vec<T>, E, G> {
+        loop {
+            match self.parse(state) {
+                ParseResult::Ok(x, pos) if pos == state.pos => {
+                    items.push(x);
+                    break;
+                }
+                ParseResult::Ok(x, pos) => {
+                    items.push(x);
+                    state = state.with_pos(pos);
+                }
+                ParseResult::Fail(..) => break,
+                ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+            }
+        }
+        ParseResult::Ok(items, state.pos)
+    }
+}
+
+/// The failure of a bounded repetition that matched fewer items than needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TooFew<F> {
+    /// How many items did match.
+    pub matched: usize,
+    /// How many items were needed.
+    pub min: usize,
+    /// Where the first missing item should have started.
+    pub pos: Pos,
+    /// Why that item failed, or `None` if the repetition stopped because an
+    /// item succeeded without consuming input.
+    pub fail: Option<F>,
+}
+
+impl<'a, T, F, E, I: ?Sized> Parser<'a, T, E, F, I> {
+    /// Repeats this parser exactly `n` times.
+    pub fn repeat_exact(self, n: usize) -> Parser<'a, Vec<T>, E, TooFew<F>, I>
+    where
+        T: 'a,
+        E: 'a,
+        F: 'a,
+    {
+        let name = format!("repeat_exact({}, {n})", &self.name);
+        self.repeat_range(n..=n).with_name(name)
+    }
+
+    /// Repeats this parser as many times as it can, up to the end of `range`,
+    /// and fails if it matched fewer times than the start of `range`.
+    /// Like `repeat_0`, it also stops after an item that consumed no input.
+    pub fn repeat_range(self, range: impl RangeBounds<usize>) -> Parser<'a, Vec<T>, E, TooFew<F>, I>
+    where
+        T: 'a,
+        E: 'a,
+        F: 'a,
+    {
+        // `None` for bounds that leave nothing in the range, like `..0`.
+        let min = match range.start_bound() {
+            Bound::Included(&n) => Some(n),
+            Bound::Excluded(&n) => n.checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+        let max = match range.end_bound() {
+            Bound::Included(&n) => Some(Some(n)),
+            Bound::Excluded(&n) => n.checked_sub(1).map(Some),
+            Bound::Unbounded => Some(None),
+        };
+        let (Some(min), Some(max)) = (min, max) else {
+            panic!("repeat_range: empty range");
+        };
+        assert!(
+            max.is_none_or(|max| min <= max),
+            "repeat_range: empty range"
+        );
+        let name = format!(
+            "repeat_range({}, {min}..={})",
+            &self.name,
+            max.map_or(String::new(), |n| n.to_string()),
+        );
+        let grammar = self.repeat_grammar(min, max);
+        Parser::from_fn(move |mut state| {
+            let mut items = Vec::new();
+            let mut fail = None;
+            while max.is_none_or(|max| items.len() < max) {
+                match self.parse(state) {
+                    ParseResult::Ok(x, pos) if pos == state.pos => {
+                        items.push(x);
+                        break;
+                    }
+                    ParseResult::Ok(x, pos) => {
+                        items.push(x);
+                        state = state.with_pos(pos);
+                    }
+                    ParseResult::Fail(f, pos) => {
+                        fail = Some((f, pos));
+                        break;
+                    }
+                    ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                    ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+                }
+            }
+            if items.len() >= min {
+                return ParseResult::Ok(items, state.pos);
+            }
+            let (fail, fail_pos) = match fail {
+                Some((f, pos)) => (Some(f), pos),
+                None => (None, state.pos),
+            };
+            let too_few = TooFew {
+                matched: items.len(),
+                min,
+                pos: state.pos,
+                fail,
+            };
+            ParseResult::Fail(too_few, fail_pos)
+        })
+        .with_name(name)
+        .with_grammar(grammar)
+    }
+
+    /// Repeats this parser until `terminator` matches, and returns the items
+    /// together with the terminator's value. The terminator is tried first,
+    /// before every item.
+    ///
+    /// Like `repeat_0`, it stops after an item that consumed no input, so
+    /// the terminator must match right after that item. If it does not, the
+    /// terminator's failure is combined with `None` (and not with the
+    /// failure of an item).
+    pub fn repeat_until<U, G, H>(
+        self,
+        terminator: Parser<'a, U, E, G, I>,
+    ) -> Parser<'a, (Vec<T>, U), E, H, I>
+    where
+        T: 'a,
+        U: 'a,
+        E: 'a,
+        F: 'a,
+        G: CombineFail<'a, Option<F>, H, I> + 'a,
+    {
+        let name = format!("repeat_until({}, {})", &self.name, &terminator.name);
+        let grammar = Grammar::Seq(vec![self.repeat_grammar(0, None), terminator.describe()]);
+        Parser::from_fn(move |mut state| {
+            let mut items = Vec::new();
+            let mut last = false;
+            loop {
+                let (g, g_pos) = match terminator.parse(state) {
+                    ParseResult::Ok(u, pos) => return ParseResult::Ok((items, u), pos),
+                    ParseResult::Fail(g, pos) => (g, pos),
+                    ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                    ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+                };
+                if last {
+                    let h = g.combine_fail(state.with_pos(g_pos), None, state);
+                    return ParseResult::Fail(h, state.pos);
+                }
+                match self.parse(state) {
+                    ParseResult::Ok(x, pos) => {
+                        items.push(x);
+                        last = pos == state.pos;
+                        state = state.with_pos(pos);
+                    }
+                    ParseResult::Fail(f, f_pos) => {
+                        let h =
+                            g.combine_fail(state.with_pos(g_pos), Some(f), state.with_pos(f_pos));
+                        return ParseResult::Fail(h, state.pos);
+                    }
+                    ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                    ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+                }
+            }
+        })
+        .with_name(name)
+        .with_grammar(grammar)
     }
 }
 
-impl<'a, T, F, E> Parser<'a, T, E, F> {
+impl<'a, T, F, E, I: ?Sized> Parser<'a, T, E, F, I> {
     #[allow(clippy::should_implement_trait)]
-    pub fn not<G>(self) -> Parser<'a, (), E, G>
+    pub fn not<G>(self) -> Parser<'a, (), E, G, I>
     where
         T: 'a,
         E: 'a,
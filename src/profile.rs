@@ -0,0 +1,148 @@
+use crate::parser::ParseResult;
+use crate::position::Pos;
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// What a profile knows about one rule (all of the parsers with the same
+/// name).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuleStats {
+    pub calls: usize,
+    /// How many times the rule failed, so some parser had to backtrack.
+    pub backtracks: usize,
+    pub errors: usize,
+    /// How much the successful calls consumed (items, for inputs that are
+    /// not text).
+    pub bytes: usize,
+    /// The time spent in the rule, including the parsers that it used. A
+    /// rule that uses itself is only timed at the outermost call.
+    pub time: Duration,
+}
+
+/// Statistics for every rule (by parser name) that ran while profiling. Turn
+/// profiling on with `Session::with_profiling`, and get the profile with
+/// `Session::take_profile`.
+///
+/// `Display` shows a table, with the slowest rules first. Give the rules
+/// that matter names with `Parser::with_name` to find them easily.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    rules: HashMap<Name, RuleStats>,
+}
+
+/// The name of a rule, which borrows as a `str` so rules can be looked up by
+/// name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Name(Rc<String>);
+
+impl Borrow<str> for Name {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Profile {
+    pub fn get(&self, name: &str) -> Option<&RuleStats> {
+        self.rules.get(name)
+    }
+
+    /// Returns every rule with its statistics, the slowest rules first.
+    pub fn rules(&self) -> Vec<(&str, &RuleStats)> {
+        let mut rules: Vec<_> = self
+            .rules
+            .iter()
+            .map(|(name, stats)| (name.0.as_str(), stats))
+            .collect();
+        rules.sort_by(|(a_name, a), (b_name, b)| b.time.cmp(&a.time).then(a_name.cmp(b_name)));
+        rules
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let header = ["rule", "calls", "backtracks", "errors", "bytes", "time"];
+        let rows: Vec<[String; 6]> = self
+            .rules()
+            .into_iter()
+            .map(|(name, stats)| {
+                [
+                    name.to_string(),
+                    stats.calls.to_string(),
+                    stats.backtracks.to_string(),
+                    stats.errors.to_string(),
+                    stats.bytes.to_string(),
+                    format!("{:?}", stats.time),
+                ]
+            })
+            .collect();
+        let mut widths = header.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let header = header.map(str::to_string);
+        for row in std::iter::once(&header).chain(&rows) {
+            // The rule name is aligned to the left, and the numbers to the
+            // right.
+            write!(f, "{:<1$}", row[0], widths[0])?;
+            for (cell, width) in row.iter().zip(widths).skip(1) {
+                write!(f, "  {cell:>width$}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects a profile in a `Session`.
+#[derive(Debug, Default)]
+pub(crate) struct Profiler {
+    on: bool,
+    /// When every parser that is still running started.
+    running: Vec<Instant>,
+    /// How many calls of every rule are running, to only time the outermost.
+    depth: HashMap<Rc<String>, usize>,
+    profile: Profile,
+}
+
+impl Profiler {
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    pub fn turn_on(&mut self) {
+        self.on = true;
+    }
+
+    pub fn enter(&mut self, name: &Rc<String>) {
+        *self.depth.entry(name.clone()).or_default() += 1;
+        self.running.push(Instant::now());
+    }
+
+    pub fn exit<T, E, F>(&mut self, name: &Rc<String>, start: Pos, result: &ParseResult<T, E, F>) {
+        let time = self.running.pop().expect("exit without enter").elapsed();
+        let depth = self.depth.get_mut(name).unwrap();
+        *depth -= 1;
+        let outermost = *depth == 0;
+        let stats = self.profile.rules.entry(Name(name.clone())).or_default();
+        stats.calls += 1;
+        match result {
+            ParseResult::Ok(_, end) => stats.bytes += end.offset - start.offset,
+            ParseResult::Fail(..) => stats.backtracks += 1,
+            ParseResult::Err(..) => stats.errors += 1,
+            ParseResult::Incomplete(_) => {}
+        }
+        if outermost {
+            stats.time += time;
+        }
+    }
+
+    pub fn take(&mut self) -> Profile {
+        std::mem::take(&mut self.profile)
+    }
+}
@@ -0,0 +1,136 @@
+use crate::input::Input;
+use crate::parser::{ParseResult, Parser};
+use crate::position::Span;
+use crate::primitives::NotFound;
+use crate::state::State;
+
+impl<'a, E: 'a, I: ?Sized + Input> Parser<'a, (), E, NotFound, I> {
+    /// Skips items (characters, for text) until `sync` matches, without
+    /// consuming `sync`. Fails if the end of input is reached first.
+    pub fn skip_until<S: 'a, G: 'a>(sync: Parser<'a, S, E, G, I>) -> Self {
+        let name = format!("skip_until({})", sync.name);
+        Parser::from_fn(move |mut state: State<'a, I>| loop {
+            let checkpoint = state.checkpoint();
+            match sync.parse(state) {
+                ParseResult::Ok(..) => {
+                    // `sync` is only looked at, so its effects are undone.
+                    state.rollback(checkpoint);
+                    return ParseResult::Ok((), state.pos);
+                }
+                ParseResult::Fail(..) => {}
+                ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+            }
+            match state.next_item() {
+                Some((_, pos)) => state = state.with_pos(pos),
+                None => return state.out_of_input(1, NotFound),
+            }
+        })
+        .with_name(name)
+    }
+}
+
+impl<'a, E: 'a> Parser<'a, (), E, NotFound> {
+    /// Skips characters until a `close` that is not matched by an earlier
+    /// `open`, without consuming it. Fails if the end of input is reached
+    /// first.
+    pub fn skip_to_delimiter(open: char, close: char) -> Self {
+        Parser::from_fn(move |mut state: State<'a>| {
+            let mut depth = 0usize;
+            while let Some((ch, pos)) = state.next_char() {
+                if ch == close && depth == 0 {
+                    return ParseResult::Ok((), state.pos);
+                } else if ch == close {
+                    depth -= 1;
+                } else if ch == open {
+                    depth += 1;
+                }
+                state = state.with_pos(pos);
+            }
+            state.out_of_input(1, NotFound)
+        })
+        .with_name(format!("skip_to_delimiter({open:?}, {close:?})"))
+    }
+}
+
+impl<'a, T, E, F, I: ?Sized> Parser<'a, T, E, F, I> {
+    /// Recovers from errors of this parser. When this parser returns an
+    /// error, `skip` is run from where the error happened, the error is
+    /// recorded in the session, and the value is `fallback` of the span that
+    /// was given up on (from the start of this parser to the end of `skip`).
+    ///
+    /// Recovery needs a place to put the error, so without a session (see
+    /// `State::with_session`), or when `skip` does not succeed, the error is
+    /// returned as is.
+    pub fn recover_with<S, G>(
+        self,
+        skip: Parser<'a, S, E, G, I>,
+        fallback: impl Fn(Span) -> T + 'a,
+    ) -> Parser<'a, T, E, F, I>
+    where
+        T: 'a,
+        E: 'static,
+        F: 'a,
+        S: 'a,
+        G: 'a,
+    {
+        let name = format!("recover_with({}, {})", self.name, skip.name);
+        self.recover(skip, false, fallback).with_name(name)
+    }
+
+    fn recover<S, G>(
+        self,
+        skip: Parser<'a, S, E, G, I>,
+        skip_from_start: bool,
+        fallback: impl Fn(Span) -> T + 'a,
+    ) -> Parser<'a, T, E, F, I>
+    where
+        T: 'a,
+        E: 'static,
+        F: 'a,
+        S: 'a,
+        G: 'a,
+    {
+        Parser::from_fn(move |state| match self.parse(state) {
+            ParseResult::Err(e, err_pos) => {
+                let Some(session) = state.session else {
+                    return ParseResult::Err(e, err_pos);
+                };
+                let skip_pos = if skip_from_start { state.pos } else { err_pos };
+                match skip.parse(state.with_pos(skip_pos)) {
+                    ParseResult::Ok(_, pos) => {
+                        session.push_error(e, err_pos);
+                        ParseResult::Ok(fallback(Span::new(state.pos, pos)), pos)
+                    }
+                    ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+                    _ => ParseResult::Err(e, err_pos),
+                }
+            }
+            result => result,
+        })
+    }
+}
+
+impl<'a, T, E, F> Parser<'a, T, E, F> {
+    /// Recovers from errors inside of a delimited block (like the arguments
+    /// between `(` and `)`), by skipping to the `close` delimiter that matches
+    /// the one before this parser. The delimiter itself is not consumed.
+    /// See `recover_with`.
+    pub fn recover_to_delimiter(
+        self,
+        open: char,
+        close: char,
+        fallback: impl Fn(Span) -> T + 'a,
+    ) -> Parser<'a, T, E, F>
+    where
+        T: 'a,
+        E: 'static,
+        F: 'a,
+    {
+        let name = format!("recover_to_delimiter({}, {open:?}, {close:?})", self.name);
+        // Skipping from the start (and not from the error) keeps track of
+        // delimiters that were opened before the error.
+        let skip = Parser::skip_to_delimiter(open, close);
+        self.recover(skip, true, fallback).with_name(name)
+    }
+}
@@ -0,0 +1,96 @@
+use crate::grammar::{Grammar, RuleName};
+use crate::parser::Parser;
+
+use std::cell::OnceCell;
+use std::rc::{Rc, Weak};
+
+/// A parser that is used before it is defined, for grammars with rules that
+/// use each other (like `expr` → `term` → `'(' expr ')'`). Get parsers that
+/// refer to it with `parser`, use them to build the definition, and then
+/// give the definition to `define`.
+///
+/// This builds every rule once, and the rules share each other instead of
+/// being made again by a function on every parse.
+pub struct ForwardDecl<'a, T, E, F = (), I: ?Sized = str> {
+    definition: Rc<OnceCell<Parser<'a, T, E, F, I>>>,
+    /// The name of the rule, for `Parser::describe`. It is only known once
+    /// it is defined.
+    rule: RuleName,
+}
+
+impl<'a, T: 'a, E: 'a, F: 'a, I: ?Sized + 'a> ForwardDecl<'a, T, E, F, I> {
+    pub fn new() -> Self {
+        ForwardDecl {
+            definition: Rc::new(OnceCell::new()),
+            rule: RuleName::new("forward_decl"),
+        }
+    }
+
+    /// Returns a parser that runs the definition.
+    ///
+    /// These parsers keep the definition alive, so rules that use each other
+    /// make a cycle of `Rc`s that is never freed. Build such a grammar once,
+    /// and keep it.
+    ///
+    /// # Panics
+    /// The returned parser panics if it runs before `define`.
+    pub fn parser(&self) -> Parser<'a, T, E, F, I> {
+        let definition = self.definition.clone();
+        self.reference(move || definition.clone())
+    }
+
+    /// Like `parser`, but the returned parser doesn't keep the definition
+    /// alive, for a parser that refers to itself (see `Parser::recursive`).
+    fn self_ref(&self) -> Parser<'a, T, E, F, I> {
+        let definition = Rc::downgrade(&self.definition);
+        self.reference(move || {
+            Weak::upgrade(&definition)
+                .expect("ForwardDecl: the parser that define returned was dropped")
+        })
+    }
+
+    fn reference<G>(&self, definition: G) -> Parser<'a, T, E, F, I>
+    where
+        G: Fn() -> Rc<OnceCell<Parser<'a, T, E, F, I>>> + 'a,
+    {
+        Parser::from_fn(move |state| {
+            let definition = definition();
+            let parser = definition
+                .get()
+                .expect("ForwardDecl: the parser ran before it was defined");
+            parser.parse(state)
+        })
+        .with_name("forward_decl")
+        .with_grammar(Grammar::Ref(self.rule.clone()))
+    }
+
+    /// Defines the parser, and returns it.
+    pub fn define(self, parser: Parser<'a, T, E, F, I>) -> Parser<'a, T, E, F, I> {
+        let name = parser.name.clone();
+        let grammar = Grammar::define(self.rule, parser.describe(), &name);
+        let definition = self.definition;
+        if definition.set(parser).is_err() {
+            unreachable!("define takes the ForwardDecl, so it is only called once");
+        }
+        Parser::from_fn(move |state| definition.get().unwrap().parse(state))
+            .with_name(name.as_str())
+            .with_grammar(grammar)
+    }
+}
+
+impl<'a, T: 'a, E: 'a, F: 'a, I: ?Sized + 'a> Default for ForwardDecl<'a, T, E, F, I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T: 'a, E: 'a, F: 'a, I: ?Sized + 'a> Parser<'a, T, E, F, I> {
+    /// Makes a parser that uses itself. `f` gets a parser that refers to the
+    /// result (`self_ref`), and returns the definition. See `ForwardDecl` for
+    /// rules that use each other.
+    pub fn recursive(f: impl FnOnce(Self) -> Self) -> Self {
+        let decl = ForwardDecl::new();
+        let definition = f(decl.self_ref());
+        decl.define(definition)
+    }
+}
@@ -0,0 +1,189 @@
+use crate::grammar::Grammar;
+use crate::parser::{ParseResult, Parser};
+use crate::state::State;
+
+/// What to do with a separator after the last item of a separated list.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Trailing {
+    /// A trailing separator is an error.
+    Forbid,
+    /// A trailing separator is consumed if it is there.
+    #[default]
+    Allow,
+    /// A non-empty list must end with a separator.
+    Require,
+}
+
+/// The error of a separated list that broke its `Trailing` policy.
+/// It is reported at the position of the (possibly missing) separator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrailingSeparator {
+    /// There was a separator after the last item, under `Trailing::Forbid`.
+    Forbidden,
+    /// There was no separator after the last item, under `Trailing::Require`.
+    Missing,
+}
+
+/// A trailing policy, together with a way to report a violation of it.
+/// `None` means a dangling separator is just left for the next parser.
+type Policy<E> = Option<(Trailing, fn(TrailingSeparator) -> E)>;
+
+impl<'a, T, E, F, I: ?Sized> Parser<'a, T, E, F, I> {
+    /// Parses zero or more items separated by `sep`.
+    /// A separator that is not followed by an item is not consumed.
+    pub fn sep_by<S, FS, G>(self, sep: Parser<'a, S, E, FS, I>) -> Parser<'a, Vec<T>, E, G, I>
+    where
+        T: 'a,
+        E: 'a,
+        F: 'a,
+        S: 'a,
+        FS: 'a,
+    {
+        let name = format!("sep_by({}, {})", &self.name, &sep.name);
+        let grammar = Grammar::Repeat {
+            inner: Box::new(self.sep_grammar(&sep)),
+            min: 0,
+            max: Some(1),
+        };
+        Parser::from_fn(move |state| match self.parse(state) {
+            ParseResult::Ok(x, pos) => self.sep_from(&sep, None, state.with_pos(pos), vec![x]),
+            ParseResult::Fail(..) => ParseResult::Ok(vec![], state.pos),
+            ParseResult::Err(e, pos) => ParseResult::Err(e, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+        })
+        .with_name(name)
+        .with_grammar(grammar)
+    }
+
+    /// Parses one or more items separated by `sep`.
+    /// A separator that is not followed by an item is not consumed.
+    pub fn sep_by_1<S, FS>(self, sep: Parser<'a, S, E, FS, I>) -> Parser<'a, Vec<T>, E, F, I>
+    where
+        T: 'a,
+        E: 'a,
+        F: 'a,
+        S: 'a,
+        FS: 'a,
+    {
+        let name = format!("sep_by_1({}, {})", &self.name, &sep.name);
+        let grammar = self.sep_grammar(&sep);
+        Parser::from_fn(move |state| match self.parse(state) {
+            ParseResult::Ok(x, pos) => self.sep_from(&sep, None, state.with_pos(pos), vec![x]),
+            ParseResult::Fail(f, pos) => ParseResult::Fail(f, pos),
+            ParseResult::Err(e, pos) => ParseResult::Err(e, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+        })
+        .with_name(name)
+        .with_grammar(grammar)
+    }
+
+    /// Parses zero or more items separated by `sep`, where a separator after
+    /// the last item is handled according to `trailing`.
+    pub fn sep_end_by<S, FS, G>(
+        self,
+        sep: Parser<'a, S, E, FS, I>,
+        trailing: Trailing,
+    ) -> Parser<'a, Vec<T>, E, G, I>
+    where
+        T: 'a,
+        E: From<TrailingSeparator> + 'a,
+        F: 'a,
+        S: 'a,
+        FS: 'a,
+    {
+        let name = format!("sep_end_by({}, {}, {trailing:?})", &self.name, &sep.name);
+        let list = match trailing {
+            Trailing::Forbid => self.sep_grammar(&sep),
+            Trailing::Allow => {
+                Grammar::Seq(vec![self.sep_grammar(&sep), sep.repeat_grammar(0, Some(1))])
+            }
+            Trailing::Require => Grammar::Repeat {
+                inner: Box::new(Grammar::Seq(vec![self.describe(), sep.describe()])),
+                min: 1,
+                max: None,
+            },
+        };
+        let grammar = Grammar::Repeat {
+            inner: Box::new(list),
+            min: 0,
+            max: Some(1),
+        };
+        Parser::from_fn(move |state| match self.parse(state) {
+            ParseResult::Ok(x, pos) => {
+                let trailing = Some((trailing, E::from as fn(_) -> _));
+                self.sep_from(&sep, trailing, state.with_pos(pos), vec![x])
+            }
+            ParseResult::Fail(..) => ParseResult::Ok(vec![], state.pos),
+            ParseResult::Err(e, pos) => ParseResult::Err(e, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+        })
+        .with_name(name)
+        .with_grammar(grammar)
+    }
+
+    /// Describes one or more items separated by `sep`.
+    fn sep_grammar<S, FS>(&self, sep: &Parser<'a, S, E, FS, I>) -> Grammar {
+        let rest = Grammar::Seq(vec![sep.describe(), self.describe()]);
+        let rest = Grammar::Repeat {
+            inner: Box::new(rest),
+            min: 0,
+            max: None,
+        };
+        Grammar::Seq(vec![self.describe(), rest])
+    }
+
+    /// Keeps parsing separators and items after the first item, until one
+    /// of them fails, or a separator and item consume nothing.
+    fn sep_from<S, FS, G>(
+        &self,
+        sep: &Parser<'a, S, E, FS, I>,
+        trailing: Policy<E>,
+        mut state: State<'a, I>,
+        mut items: Vec<T>,
+    ) -> ParseResult<Vec<T>, E, G> {
+        loop {
+            let checkpoint = state.checkpoint();
+            let after_sep = match sep.parse(state) {
+                ParseResult::Ok(_, pos) => pos,
+                ParseResult::Fail(..) => {
+                    return match trailing {
+                        Some((Trailing::Require, to_err)) => {
+                            ParseResult::Err(to_err(TrailingSeparator::Missing), state.pos)
+                        }
+                        _ => ParseResult::Ok(items, state.pos),
+                    };
+                }
+                ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+            };
+            match self.parse(state.with_pos(after_sep)) {
+                // Like in `repeat_0`, an item that consumed nothing is the last.
+                ParseResult::Ok(x, pos) if pos == state.pos => {
+                    items.push(x);
+                    return ParseResult::Ok(items, pos);
+                }
+                ParseResult::Ok(x, pos) => {
+                    items.push(x);
+                    state = state.with_pos(pos);
+                }
+                ParseResult::Fail(..) => {
+                    return match trailing {
+                        None => {
+                            // The separator is left for the next parser.
+                            state.rollback(checkpoint);
+                            ParseResult::Ok(items, state.pos)
+                        }
+                        Some((Trailing::Forbid, to_err)) => {
+                            ParseResult::Err(to_err(TrailingSeparator::Forbidden), state.pos)
+                        }
+                        Some((Trailing::Allow | Trailing::Require, _)) => {
+                            ParseResult::Ok(items, after_sep)
+                        }
+                    };
+                }
+                ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                ParseResult::Incomplete(needed) => return ParseResult::Incomplete(needed),
+            }
+        }
+    }
+}
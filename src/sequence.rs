@@ -0,0 +1,197 @@
+use crate::grammar::Grammar;
+use crate::parser::{ParseResult, Parser};
+use crate::{CombineFail, CombineManyFail};
+
+/// A tuple of parsers (with up to 12 parsers) that can run one after the
+/// other with `Parser::seq`.
+///
+/// The parsers all fail with the same type. Only one of them can fail, and
+/// its failure is given (on its own) to `CombineManyFail`, which makes the
+/// failure `H` of the sequence.
+pub trait Sequence<'a, E, H, I: ?Sized = str> {
+    /// The values of all of the parsers, as a tuple.
+    type Output;
+
+    fn sequence(self) -> Parser<'a, Self::Output, E, H, I>;
+}
+
+macro_rules! sequence {
+    ( $( $parser:ident $value:ident: $t:ident ),+ ) => {
+        impl<'a, E, F, H, I: ?Sized, $($t),+> Sequence<'a, E, H, I>
+            for ( $(Parser<'a, $t, E, F, I>,)+ )
+        where
+            E: 'a,
+            F: CombineManyFail<'a, H, I> + 'a,
+            H: 'a,
+            $( $t: 'a, )+
+        {
+            type Output = ( $($t,)+ );
+
+            fn sequence(self) -> Parser<'a, Self::Output, E, H, I> {
+                let ( $($parser,)+ ) = self;
+                let names = [ $($parser.name.as_str()),+ ];
+                let name = format!("seq({})", names.join(", "));
+                let grammar = Grammar::seq([ $($parser.describe()),+ ]);
+                Parser::from_fn(move |state| {
+                    let mut pos = state.pos;
+                    $(
+                        let $value = match $parser.parse(state.with_pos(pos)) {
+                            ParseResult::Ok(value, end) => {
+                                pos = end;
+                                value
+                            }
+                            ParseResult::Fail(f, pos) => {
+                                let h = F::combine_many_fail(vec![(f, state.with_pos(pos))]);
+                                return ParseResult::Fail(h, pos);
+                            }
+                            ParseResult::Err(e, pos) => return ParseResult::Err(e, pos),
+                            ParseResult::Incomplete(needed) => {
+                                return ParseResult::Incomplete(needed)
+                            }
+                        };
+                    )+
+                    ParseResult::Ok(( $($value,)+ ), pos)
+                })
+                .with_name(name)
+                .with_grammar(grammar)
+            }
+        }
+    };
+}
+
+sequence!(p1 v1: T1);
+sequence!(p1 v1: T1, p2 v2: T2);
+sequence!(p1 v1: T1, p2 v2: T2, p3 v3: T3);
+sequence!(p1 v1: T1, p2 v2: T2, p3 v3: T3, p4 v4: T4);
+sequence!(p1 v1: T1, p2 v2: T2, p3 v3: T3, p4 v4: T4, p5 v5: T5);
+sequence!(
+    p1 v1: T1, p2 v2: T2, p3 v3: T3, p4 v4: T4, p5 v5: T5, p6 v6: T6
+);
+sequence!(
+    p1 v1: T1, p2 v2: T2, p3 v3: T3, p4 v4: T4, p5 v5: T5, p6 v6: T6,
+    p7 v7: T7
+);
+sequence!(
+    p1 v1: T1, p2 v2: T2, p3 v3: T3, p4 v4: T4, p5 v5: T5, p6 v6: T6,
+    p7 v7: T7, p8 v8: T8
+);
+sequence!(
+    p1 v1: T1, p2 v2: T2, p3 v3: T3, p4 v4: T4, p5 v5: T5, p6 v6: T6,
+    p7 v7: T7, p8 v8: T8, p9 v9: T9
+);
+sequence!(
+    p1 v1: T1, p2 v2: T2, p3 v3: T3, p4 v4: T4, p5 v5: T5, p6 v6: T6,
+    p7 v7: T7, p8 v8: T8, p9 v9: T9, p10 v10: T10
+);
+sequence!(
+    p1 v1: T1, p2 v2: T2, p3 v3: T3, p4 v4: T4, p5 v5: T5, p6 v6: T6,
+    p7 v7: T7, p8 v8: T8, p9 v9: T9, p10 v10: T10, p11 v11: T11
+);
+sequence!(
+    p1 v1: T1, p2 v2: T2, p3 v3: T3, p4 v4: T4, p5 v5: T5, p6 v6: T6,
+    p7 v7: T7, p8 v8: T8, p9 v9: T9, p10 v10: T10, p11 v11: T11,
+    p12 v12: T12
+);
+
+impl<'a, T, E, F, I: ?Sized> Parser<'a, T, E, F, I> {
+    /// Runs a tuple of parsers one after the other, and returns a tuple of
+    /// their values: `Parser::seq((a, b, c))`. See `Sequence` for the failure
+    /// type.
+    pub fn seq<S>(parsers: S) -> Self
+    where
+        S: Sequence<'a, E, F, I, Output = T>,
+    {
+        parsers.sequence()
+    }
+
+    /// Runs `other` after this parser, and returns both values.
+    ///
+    /// Only one of them can fail. Its failure is combined (with
+    /// `CombineFail`) as `Some`, with `None` for the other one.
+    pub fn then<U, G, H>(self, other: Parser<'a, U, E, G, I>) -> Parser<'a, (T, U), E, H, I>
+    where
+        T: 'a,
+        U: 'a,
+        E: 'a,
+        F: 'a,
+        G: 'a,
+        Option<F>: CombineFail<'a, Option<G>, H, I>,
+    {
+        let name = format!("then({}, {})", self.name, other.name);
+        let grammar = Grammar::seq([self.describe(), other.describe()]);
+        Parser::from_fn(move |state| match self.parse(state) {
+            ParseResult::Ok(x, pos) => match other.parse(state.with_pos(pos)) {
+                ParseResult::Ok(y, end) => ParseResult::Ok((x, y), end),
+                ParseResult::Fail(g, end) => {
+                    let h = None.combine_fail(state, Some(g), state.with_pos(end));
+                    ParseResult::Fail(h, end)
+                }
+                ParseResult::Err(e, end) => ParseResult::Err(e, end),
+                ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+            },
+            ParseResult::Fail(f, pos) => {
+                let state = state.with_pos(pos);
+                ParseResult::Fail(Some(f).combine_fail(state, None, state), pos)
+            }
+            ParseResult::Err(e, pos) => ParseResult::Err(e, pos),
+            ParseResult::Incomplete(needed) => ParseResult::Incomplete(needed),
+        })
+        .with_name(name)
+        .with_grammar(grammar)
+    }
+
+    /// Runs `other` after this parser, and keeps the value of this parser.
+    /// The failures are combined like in `then`.
+    pub fn skip<U, G, H>(self, other: Parser<'a, U, E, G, I>) -> Parser<'a, T, E, H, I>
+    where
+        T: 'a,
+        U: 'a,
+        E: 'a,
+        F: 'a,
+        G: 'a,
+        Option<F>: CombineFail<'a, Option<G>, H, I>,
+        H: 'a,
+    {
+        let name = format!("skip({}, {})", self.name, other.name);
+        self.then(other).map(|(x, _)| x).with_name(name)
+    }
+
+    /// Runs `other` before this parser, and keeps the value of this parser.
+    /// The failures are combined like in `then`, with the failure of `other`
+    /// first.
+    pub fn preceded_by<U, G, H>(self, other: Parser<'a, U, E, G, I>) -> Parser<'a, T, E, H, I>
+    where
+        T: 'a,
+        U: 'a,
+        E: 'a,
+        F: 'a,
+        G: 'a,
+        Option<G>: CombineFail<'a, Option<F>, H, I>,
+        H: 'a,
+    {
+        let name = format!("preceded_by({}, {})", self.name, other.name);
+        other.then(self).map(|(_, x)| x).with_name(name)
+    }
+
+    /// Runs `open`, `parser` and `close`, and keeps the value of `parser`,
+    /// like for something in parentheses. The failures are combined like in
+    /// `seq`.
+    pub fn delimited<A, B, H>(
+        open: Parser<'a, A, E, F, I>,
+        parser: Self,
+        close: Parser<'a, B, E, F, I>,
+    ) -> Parser<'a, T, E, H, I>
+    where
+        T: 'a,
+        A: 'a,
+        B: 'a,
+        E: 'a,
+        F: CombineManyFail<'a, H, I> + 'a,
+        H: 'a,
+    {
+        let name = format!("delimited({}, {}, {})", open.name, parser.name, close.name);
+        Parser::seq((open, parser, close))
+            .map(|(_, x, _)| x)
+            .with_name(name)
+    }
+}
@@ -0,0 +1,300 @@
+use crate::position::Pos;
+use crate::profile::{Profile, Profiler};
+use crate::trace::{Trace, Tracer};
+
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell, RefMut};
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Bookkeeping shared by all of the parsers in a single parse run.
+/// Attach one to the state with `State::with_session`.
+///
+/// Everything in a session is rolled back when a parser fails, so a parser
+/// that returns `ParseResult::Fail` never leaves anything behind. This way,
+/// an alternative that `or` gave up on does not leave stray errors or
+/// context changes.
+pub struct Session {
+    /// Tells sessions apart, so parsers that keep things between runs (like
+    /// `Parser::memoize`) know when a new run started.
+    id: u64,
+    /// How many parsers are running, and how many times a parser started
+    /// when none were (see `Session::run`).
+    running: Cell<usize>,
+    runs: Cell<u64>,
+    errors: RefCell<Vec<(Box<dyn Any>, Pos)>>,
+    contexts: RefCell<Contexts>,
+    trace: RefCell<Tracer>,
+    profile: RefCell<Profiler>,
+}
+
+/// User contexts, one per type. `log` has the changes that a rollback may
+/// need to undo, with the values they replaced. A replaced value is only
+/// kept while a checkpoint that can roll back to it is alive, so updating a
+/// context many times does not keep every old copy of it.
+#[derive(Default)]
+struct Contexts {
+    values: HashMap<TypeId, Rc<dyn Any>>,
+    log: Vec<Change>,
+    /// The last change of every type in `log`.
+    last: HashMap<TypeId, usize>,
+    /// The checkpoints that may still be rolled back to, by their position
+    /// in `log`. A checkpoint is alive while it holds its mark.
+    marks: Vec<(usize, Weak<()>)>,
+    /// How many times a context was set (minus the ones rolled back), so
+    /// checkpoints tell changes apart even if they are not in `log`.
+    changes: usize,
+}
+
+struct Change {
+    type_id: TypeId,
+    /// The value before the change. This is `None` if there was no value,
+    /// and also when no checkpoint can roll back to between this change and
+    /// the one before it (`prev`), since undoing that one restores the value.
+    old: Option<Rc<dyn Any>>,
+    /// The change of the same type before this one.
+    prev: Option<usize>,
+}
+
+/// A point in a session to roll back to. See `Session::checkpoint`.
+/// The session keeps what it needs to roll back to a checkpoint until the
+/// checkpoint is dropped.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Checkpoint {
+    errors: usize,
+    contexts: usize,
+    changes: usize,
+    /// Tells the session that this checkpoint is alive (see
+    /// `Contexts::marks`).
+    mark: Option<Rc<()>>,
+}
+
+impl Contexts {
+    /// Returns a mark for a checkpoint at the end of `log`.
+    fn mark(&mut self) -> Rc<()> {
+        while let Some((_, mark)) = self.marks.last() {
+            if mark.strong_count() > 0 {
+                break;
+            }
+            self.marks.pop();
+        }
+        let pos = self.log.len();
+        if let Some((last, mark)) = self.marks.last() {
+            if *last == pos {
+                return mark.upgrade().unwrap();
+            }
+        }
+        let mark = Rc::new(());
+        self.marks.push((pos, Rc::downgrade(&mark)));
+        mark
+    }
+
+    /// Whether a checkpoint that is alive is after the change `after` (or
+    /// anywhere, if it is `None`), and not after the change `until`.
+    fn is_marked(&mut self, after: Option<usize>, until: usize) -> bool {
+        self.marks.retain(|(_, mark)| mark.strong_count() > 0);
+        let mut marks = self.marks.iter();
+        marks.any(|&(pos, _)| after.is_none_or(|after| pos > after) && pos <= until)
+    }
+
+    fn set(&mut self, type_id: TypeId, value: Rc<dyn Any>) {
+        self.changes += 1;
+        let old = self.values.insert(type_id, value);
+        let prev = self.last.get(&type_id).copied();
+        // If every checkpoint would undo the change before this one anyway,
+        // this one does not need to be undone on its own.
+        if !self.is_marked(prev, self.log.len()) {
+            return;
+        }
+        if let Some(prev) = prev {
+            let before = self.log[prev].prev;
+            if before.is_some() && !self.is_marked(before, prev) {
+                self.log[prev].old = None;
+            }
+        }
+        self.last.insert(type_id, self.log.len());
+        self.log.push(Change { type_id, old, prev });
+    }
+
+    fn rollback(&mut self, checkpoint: &Checkpoint) {
+        while self.log.len() > checkpoint.contexts {
+            let change = self.log.pop().unwrap();
+            match (change.old, change.prev) {
+                (Some(old), _) => {
+                    self.values.insert(change.type_id, old);
+                }
+                (None, None) => {
+                    self.values.remove(&change.type_id);
+                }
+                // The change before this one is undone too, and restores the
+                // value.
+                (None, Some(_)) => {}
+            }
+            match change.prev {
+                Some(prev) => self.last.insert(change.type_id, prev),
+                None => self.last.remove(&change.type_id),
+            };
+        }
+        self.marks.retain(|&(pos, _)| pos <= checkpoint.contexts);
+        self.changes = checkpoint.changes;
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Session {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            running: Cell::default(),
+            runs: Cell::default(),
+            errors: RefCell::default(),
+            contexts: RefCell::default(),
+            trace: RefCell::default(),
+            profile: RefCell::default(),
+        }
+    }
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Session::default()
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Tells the runs of this session apart. A run is everything that
+    /// happens in one call to `Parser::parse` that is not inside another.
+    pub(crate) fn run(&self) -> u64 {
+        self.runs.get()
+    }
+
+    /// Called by `Parser::parse` for every parser, which keeps the guard
+    /// until it is done.
+    pub(crate) fn enter(&self) -> Running<'_> {
+        if self.running.get() == 0 {
+            self.runs.set(self.runs.get() + 1);
+        }
+        self.running.set(self.running.get() + 1);
+        Running(self)
+    }
+
+    /// Records an error that the parser recovered from.
+    pub fn push_error<E: 'static>(&self, error: E, pos: Pos) {
+        self.errors.borrow_mut().push((Box::new(error), pos));
+    }
+
+    /// Returns the recorded errors of type `E`, in the order they were
+    /// recorded.
+    pub fn errors<E: Clone + 'static>(&self) -> Vec<(E, Pos)> {
+        let errors = self.errors.borrow();
+        let errors = errors.iter();
+        errors
+            .filter_map(|(e, pos)| Some((e.downcast_ref::<E>()?.clone(), *pos)))
+            .collect()
+    }
+
+    /// Like `errors`, but removes them from the session.
+    pub fn take_errors<E: 'static>(&self) -> Vec<(E, Pos)> {
+        let (taken, kept) = self
+            .errors
+            .take()
+            .into_iter()
+            .partition::<Vec<_>, _>(|(e, _)| e.is::<E>());
+        *self.errors.borrow_mut() = kept;
+        taken
+            .into_iter()
+            .map(|(e, pos)| (*e.downcast::<E>().unwrap(), pos))
+            .collect()
+    }
+
+    /// Sets the context of type `C`. Parsers can get to it with
+    /// `Parser::context`.
+    pub fn with_context<C: 'static>(self, context: C) -> Self {
+        self.set_context(context);
+        self
+    }
+
+    /// Sets the context of type `C`, replacing the previous one.
+    pub fn set_context<C: 'static>(&self, context: C) {
+        self.replace_context(Rc::new(context));
+    }
+
+    pub(crate) fn replace_context<C: 'static>(&self, context: Rc<C>) {
+        let mut contexts = self.contexts.borrow_mut();
+        contexts.set(TypeId::of::<C>(), context);
+    }
+
+    /// Returns the current context of type `C`, if one was set.
+    pub fn context<C: 'static>(&self) -> Option<Rc<C>> {
+        let contexts = self.contexts.borrow();
+        let context = contexts.values.get(&TypeId::of::<C>())?;
+        Some(context.clone().downcast::<C>().unwrap())
+    }
+
+    /// Traces every parser that runs with this session. See `Trace`.
+    pub fn with_tracing(self) -> Self {
+        self.tracer().turn_on();
+        self
+    }
+
+    /// Returns the trace so far, and starts a new one.
+    pub fn take_trace(&self) -> Trace {
+        self.tracer().take()
+    }
+
+    pub(crate) fn tracer(&self) -> RefMut<'_, Tracer> {
+        self.trace.borrow_mut()
+    }
+
+    /// Profiles every parser that runs with this session. See `Profile`.
+    pub fn with_profiling(self) -> Self {
+        self.profiler().turn_on();
+        self
+    }
+
+    /// Returns the profile so far, and starts a new one.
+    pub fn take_profile(&self) -> Profile {
+        self.profiler().take()
+    }
+
+    pub(crate) fn profiler(&self) -> RefMut<'_, Profiler> {
+        self.profile.borrow_mut()
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        let errors = self.errors.borrow().len();
+        let mut contexts = self.contexts.borrow_mut();
+        Checkpoint {
+            errors,
+            contexts: contexts.log.len(),
+            changes: contexts.changes,
+            mark: Some(contexts.mark()),
+        }
+    }
+
+    /// Forgets everything that happened since `checkpoint`.
+    pub fn rollback(&self, checkpoint: Checkpoint) {
+        self.errors.borrow_mut().truncate(checkpoint.errors);
+        self.contexts.borrow_mut().rollback(&checkpoint);
+    }
+}
+
+/// A parser that is running. See `Session::enter`.
+pub(crate) struct Running<'s>(&'s Session);
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        self.0.running.set(self.0.running.get() - 1);
+    }
+}
+
+impl std::fmt::Debug for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let errors = self.errors.borrow().len();
+        let contexts = self.contexts.borrow().values.len();
+        write!(f, "Session({errors} errors, {contexts} contexts)")
+    }
+}
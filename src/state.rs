@@ -1,32 +1,170 @@
-use crate::position::Pos;
+use crate::input::Input;
+use crate::parser::ParseResult;
+use crate::position::{ColumnMode, Pos, Span};
+use crate::session::{Checkpoint, Session};
 
-#[derive(Debug, Clone, Copy)]
-pub struct State<'input> {
-    pub text: &'input str,
+/// Where a parser is in its input. `I` is the input that is parsed, which is
+/// source text by default.
+pub struct State<'input, I: ?Sized = str> {
+    pub text: &'input I,
     pub pos: Pos,
+    pub column_mode: ColumnMode,
+    pub session: Option<&'input Session>,
+    /// Is `text` only the start of the input, with more of it to come?
+    pub partial: bool,
 }
 
-impl<'input> State<'input> {
+// Implemented by hand because the derives would require `I: Clone` and such,
+// which `str` and slices are not.
+impl<I: ?Sized> Clone for State<'_, I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<I: ?Sized> Copy for State<'_, I> {}
+
+impl<I: ?Sized + std::fmt::Debug> std::fmt::Debug for State<'_, I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("State")
+            .field("text", &self.text)
+            .field("pos", &self.pos)
+            .field("column_mode", &self.column_mode)
+            .field("session", &self.session)
+            .field("partial", &self.partial)
+            .finish()
+    }
+}
+
+impl<'input> State<'input, str> {
     pub const fn new(text: &'input str) -> Self {
         let pos = Pos::start();
-        State { text, pos }
+        State::at(text, pos)
     }
 
     pub const fn at(text: &'input str, pos: Pos) -> Self {
-        State { text, pos }
+        State::of(text).with_pos(pos)
+    }
+}
+
+impl<'input, I: ?Sized> State<'input, I> {
+    /// The start of any input, like a slice of tokens or bytes. For text,
+    /// this is `State::new`.
+    pub const fn of(text: &'input I) -> Self {
+        let pos = Pos::start();
+        let column_mode = ColumnMode::Chars;
+        State {
+            text,
+            pos,
+            column_mode,
+            session: None,
+            partial: false,
+        }
     }
 
     pub const fn with_pos(self, pos: Pos) -> Self {
         State { pos, ..self }
     }
 
-    pub fn rest(&self) -> &'input str {
-        &self.text[self.pos.offset..]
+    pub const fn with_column_mode(self, column_mode: ColumnMode) -> Self {
+        State {
+            column_mode,
+            ..self
+        }
+    }
+
+    pub const fn with_session(self, session: &'input Session) -> Self {
+        let session = Some(session);
+        State { session, ..self }
+    }
+
+    /// Marks the text as only the start of the input. Then, parsers that get
+    /// to its end return `ParseResult::Incomplete` instead of failing.
+    pub const fn with_partial(self, partial: bool) -> Self {
+        State { partial, ..self }
     }
 
-    pub const fn eof(&self) -> bool {
+    /// What a parser returns when it needs `needed` more items than there
+    /// are: `Incomplete` for a partial input, and otherwise `fail`.
+    pub fn out_of_input<T, E, F>(&self, needed: usize, fail: F) -> ParseResult<T, E, F> {
+        if self.partial {
+            ParseResult::Incomplete(needed)
+        } else {
+            ParseResult::Fail(fail, self.pos)
+        }
+    }
+
+    /// Returns a checkpoint of the session, if there is one.
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.session.map(Session::checkpoint).unwrap_or_default()
+    }
+
+    /// Rolls the session (if there is one) back to `checkpoint`.
+    pub fn rollback(&self, checkpoint: Checkpoint) {
+        if let Some(session) = self.session {
+            session.rollback(checkpoint);
+        }
+    }
+}
+
+impl<'input, I: ?Sized + Input> State<'input, I> {
+    pub fn rest(&self) -> I::Slice<'input> {
+        self.text.slice(self.pos.offset, self.text.len())
+    }
+
+    /// Returns the part of the input that `span` covers.
+    pub fn slice(&self, span: Span) -> I::Slice<'input> {
+        self.text.slice(span.start.offset, span.end.offset)
+    }
+
+    pub fn eof(&self) -> bool {
         self.pos.offset >= self.text.len()
     }
+
+    /// Returns the next item, and the position right after it.
+    /// This is the only place that knows how to move a position over the
+    /// input, so every primitive should go through here (or `State::skip`).
+    pub fn next_item(&self) -> Option<(I::Item<'input>, Pos)> {
+        self.text.next_item(self.pos, self.column_mode)
+    }
+}
+
+impl<'input> State<'input> {
+    /// Returns the next character, and the position right after it.
+    pub fn next_char(&self) -> Option<(char, Pos)> {
+        self.next_item()
+    }
+
+    /// Returns the position after skipping `bytes` bytes from the current
+    /// position. `bytes` must land on a character boundary.
+    pub fn skip(&self, bytes: usize) -> Pos {
+        let end = self.pos.offset + bytes;
+        assert!(
+            self.text.is_char_boundary(end),
+            "skipping {bytes} bytes from {} does not land on a character boundary",
+            self.pos,
+        );
+        let mut state = *self;
+        while state.pos.offset < end {
+            let (_, pos) = state.next_char().unwrap();
+            state = state.with_pos(pos);
+        }
+        state.pos
+    }
+}
+
+impl<T> State<'_, [T]> {
+    /// Returns the position after skipping `n` items from the current
+    /// position.
+    pub fn skip(&self, n: usize) -> Pos {
+        let offset = self.pos.offset + n;
+        assert!(
+            offset <= self.text.len(),
+            "skipping {n} items from {} goes past the end",
+            self.pos,
+        );
+        Pos { offset, ..self.pos }
+    }
 }
 
 impl<'input, T: Into<&'input str>> From<T> for State<'input> {
@@ -34,3 +172,9 @@ impl<'input, T: Into<&'input str>> From<T> for State<'input> {
         State::new(text.into())
     }
 }
+
+impl<'input, T> From<&'input [T]> for State<'input, [T]> {
+    fn from(items: &'input [T]) -> Self {
+        State::of(items)
+    }
+}
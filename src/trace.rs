@@ -0,0 +1,180 @@
+use crate::parser::ParseResult;
+use crate::position::Pos;
+
+use std::fmt::{self, Display, Write};
+use std::rc::Rc;
+
+/// How a parser finished, without the values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Ok,
+    Fail,
+    Err,
+    Incomplete,
+}
+
+impl Outcome {
+    /// Returns the outcome of `result`, and the position it ended at.
+    pub fn of<T, E, F>(result: &ParseResult<T, E, F>) -> (Outcome, Option<Pos>) {
+        match result {
+            ParseResult::Ok(_, pos) => (Outcome::Ok, Some(*pos)),
+            ParseResult::Fail(_, pos) => (Outcome::Fail, Some(*pos)),
+            ParseResult::Err(_, pos) => (Outcome::Err, Some(*pos)),
+            ParseResult::Incomplete(_) => (Outcome::Incomplete, None),
+        }
+    }
+}
+
+/// A single run of a parser in a trace, with the runs of the parsers it
+/// used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceNode {
+    pub name: Rc<String>,
+    pub start: Pos,
+    pub outcome: Outcome,
+    /// Where the parser stopped. `None` when it needed more input.
+    pub end: Option<Pos>,
+    pub children: Vec<TraceNode>,
+}
+
+/// A tree of every parser that ran while tracing, in the order they ran.
+/// Turn tracing on with `Session::with_tracing` or `Parser::traced`, and get
+/// the trace with `Session::take_trace`.
+///
+/// `Display` shows the trace as an indented tree, and `to_json` as JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub roots: Vec<TraceNode>,
+}
+
+impl Trace {
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Returns the trace as a JSON array of nodes, where every node looks
+    /// like this:
+    /// `{"name": "char", "start": {"offset": 0, "row": 1, "col": 1},
+    /// "outcome": "Ok", "end": {...} or null, "children": [...]}`.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        write_json_nodes(&mut json, &self.roots);
+        json
+    }
+}
+
+fn write_json_nodes(json: &mut String, nodes: &[TraceNode]) {
+    json.push('[');
+    for (i, node) in nodes.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str("{\"name\":");
+        write_json_string(json, &node.name);
+        json.push_str(",\"start\":");
+        write_json_pos(json, node.start);
+        write!(json, ",\"outcome\":\"{:?}\",\"end\":", node.outcome).unwrap();
+        match node.end {
+            Some(end) => write_json_pos(json, end),
+            None => json.push_str("null"),
+        }
+        json.push_str(",\"children\":");
+        write_json_nodes(json, &node.children);
+        json.push('}');
+    }
+    json.push(']');
+}
+
+fn write_json_pos(json: &mut String, Pos { offset, row, col }: Pos) {
+    write!(json, "{{\"offset\":{offset},\"row\":{row},\"col\":{col}}}").unwrap();
+}
+
+fn write_json_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// Every node takes a line:
+/// `name 1:1 -> Ok 1:4`, with the children indented under it.
+impl Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_node(f: &mut fmt::Formatter, node: &TraceNode, depth: usize) -> fmt::Result {
+            let indent = "  ".repeat(depth);
+            let start = node.start;
+            write!(f, "{indent}{} {}:{}", node.name, start.row, start.col)?;
+            write!(f, " -> {:?}", node.outcome)?;
+            if let Some(end) = node.end {
+                write!(f, " {}:{}", end.row, end.col)?;
+            }
+            writeln!(f)?;
+            for child in &node.children {
+                write_node(f, child, depth + 1)?;
+            }
+            Ok(())
+        }
+        for root in &self.roots {
+            write_node(f, root, 0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects a trace in a `Session`.
+#[derive(Debug, Default)]
+pub(crate) struct Tracer {
+    /// How many times tracing was turned on (`Parser::traced` can be
+    /// nested).
+    on: usize,
+    /// The nodes of the parsers that are still running, and their children
+    /// so far.
+    running: Vec<(Rc<String>, Pos, Vec<TraceNode>)>,
+    trace: Trace,
+}
+
+impl Tracer {
+    pub fn is_on(&self) -> bool {
+        self.on > 0
+    }
+
+    pub fn turn_on(&mut self) {
+        self.on += 1;
+    }
+
+    pub fn turn_off(&mut self) {
+        self.on -= 1;
+    }
+
+    pub fn enter(&mut self, name: Rc<String>, start: Pos) {
+        self.running.push((name, start, vec![]));
+    }
+
+    pub fn exit(&mut self, outcome: Outcome, end: Option<Pos>) {
+        let (name, start, children) = self.running.pop().expect("exit without enter");
+        let node = TraceNode {
+            name,
+            start,
+            outcome,
+            end,
+            children,
+        };
+        match self.running.last_mut() {
+            Some((_, _, siblings)) => siblings.push(node),
+            None => self.trace.roots.push(node),
+        }
+    }
+
+    pub fn take(&mut self) -> Trace {
+        std::mem::take(&mut self.trace)
+    }
+}
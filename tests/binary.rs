@@ -0,0 +1,135 @@
+use nessie_parse::{BinaryFailure, ParseResult, Parser};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+type BinaryParser<'a, T> = Parser<'a, T, Error, BinaryFailure, [u8]>;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Header<'a> {
+    version: u8,
+    flags: u16,
+    size: u32,
+    scale: f32,
+    count: u64,
+    name: &'a [u8],
+}
+
+/// `NSP`, then the fields of the header in order, and then the name as a
+/// block of bytes with a one byte length.
+fn header<'a>() -> BinaryParser<'a, Header<'a>> {
+    Parser::tag(b"NSP").and_then(|()| {
+        Parser::u8().and_then(|version| {
+            Parser::u16_le().and_then(move |flags| {
+                Parser::u32_be().and_then(move |size| {
+                    Parser::f32_le().and_then(move |scale| {
+                        Parser::varint().and_then(move |count| {
+                            let len = Parser::u8();
+                            let name = Parser::take(3);
+                            Parser::length_prefixed(len, name).map(move |name| Header {
+                                version,
+                                flags,
+                                size,
+                                scale,
+                                count,
+                                name,
+                            })
+                        })
+                    })
+                })
+            })
+        })
+    })
+}
+
+fn header_bytes() -> Vec<u8> {
+    let mut bytes = b"NSP\x02".to_vec();
+    bytes.extend(0x0102u16.to_le_bytes());
+    bytes.extend(0x0a0b0c0du32.to_be_bytes());
+    bytes.extend(1.5f32.to_le_bytes());
+    bytes.extend([0xac, 0x02]);
+    bytes.extend(b"\x03abc");
+    bytes
+}
+
+#[test]
+fn parses_a_header() {
+    let bytes = header_bytes();
+    let ParseResult::Ok(header, pos) = header().parse(bytes.as_slice().into()) else {
+        panic!("expected Ok");
+    };
+    assert_eq!(
+        header,
+        Header {
+            version: 2,
+            flags: 0x0102,
+            size: 0x0a0b0c0d,
+            scale: 1.5,
+            count: 300,
+            name: b"abc",
+        }
+    );
+    assert_eq!(pos.offset, bytes.len());
+}
+
+#[test]
+fn failures_report_byte_offsets() {
+    let bytes = header_bytes();
+    // Cut the input in the middle of the `u32`.
+    let result = header().parse(bytes[..8].into());
+    assert!(matches!(
+        result,
+        ParseResult::Fail(BinaryFailure::Eof { needed: 2 }, pos) if pos.offset == 6
+    ));
+
+    let result = header().parse(b"NSQ".as_slice().into());
+    assert!(matches!(result, ParseResult::Fail(BinaryFailure::BadTag, pos) if pos.offset == 0));
+    let result = header().parse(b"NS".as_slice().into());
+    assert!(matches!(
+        result,
+        ParseResult::Fail(BinaryFailure::Eof { needed: 1 }, _)
+    ));
+}
+
+#[test]
+fn varints() {
+    let varint = BinaryParser::varint;
+    assert!(matches!(
+        varint().parse([0x05].as_slice().into()),
+        ParseResult::Ok(5, _)
+    ));
+    let max = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+    assert!(matches!(
+        varint().parse(max.as_slice().into()),
+        ParseResult::Ok(u64::MAX, pos) if pos.offset == 10
+    ));
+    let too_big = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+    assert!(matches!(
+        varint().parse(too_big.as_slice().into()),
+        ParseResult::Fail(BinaryFailure::Overflow, _)
+    ));
+    assert!(matches!(
+        varint().parse([0x80].as_slice().into()),
+        ParseResult::Fail(BinaryFailure::Eof { needed: 1 }, _)
+    ));
+}
+
+#[test]
+fn length_prefixed_blocks_are_bounded() {
+    let block = || BinaryParser::length_prefixed(Parser::u8(), Parser::u16_be());
+    let result = block().parse([2, 0x12, 0x34, 0xff].as_slice().into());
+    assert!(matches!(result, ParseResult::Ok(0x1234, pos) if pos.offset == 3));
+
+    // The `u16` can't read past the end of the block.
+    let result = block().parse([1, 0x12, 0x34].as_slice().into());
+    assert!(matches!(
+        result,
+        ParseResult::Fail(BinaryFailure::Eof { needed: 1 }, pos) if pos.offset == 1
+    ));
+
+    let result = block().parse([3, 0x12, 0x34, 0xff].as_slice().into());
+    assert!(matches!(
+        result,
+        ParseResult::Fail(BinaryFailure::Unused { left: 1 }, pos) if pos.offset == 3
+    ));
+}
@@ -0,0 +1,141 @@
+use nessie_parse::{ParseResult, Parser, Session, State};
+
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Error {
+    Undefined,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Symbols(Vec<String>);
+
+fn char_eq<'a>(ch: char) -> Parser<'a, char, Error> {
+    Parser::char_eq(ch)
+}
+
+fn name<'a>() -> Parser<'a, &'a str, Error> {
+    Parser::letter().repeat_1().recognize().map_fail(|_| ())
+}
+
+/// `+name` declares a name.
+fn declare<'a>() -> Parser<'a, (), Error> {
+    char_eq('+').and_then(|_| name()).and_then(|name| {
+        let name = name.to_string();
+        Parser::update_context(move |symbols: &mut Symbols| symbols.0.push(name.clone()))
+    })
+}
+
+/// `name` uses a name, which must have been declared.
+fn use_name<'a>() -> Parser<'a, (), Error> {
+    name().and_then(|name| {
+        Parser::context().and_then(move |symbols: Rc<Symbols>| {
+            if symbols.0.iter().any(|s| s == name) {
+                Parser::ret(())
+            } else {
+                Parser::err(Error::Undefined)
+            }
+        })
+    })
+}
+
+fn program<'a>() -> Parser<'a, Vec<()>, Error> {
+    declare()
+        .or(use_name())
+        .map_fail(|((), ())| ())
+        .and_then(|()| char_eq(';'))
+        .map(|_| ())
+        .repeat_0()
+}
+
+#[test]
+fn declared_names_can_be_used() {
+    let session = Session::new().with_context(Symbols::default());
+    let state = State::new("+a;+b;a;b;").with_session(&session);
+    assert!(matches!(program().parse(state), ParseResult::Ok(v, _) if v.len() == 4));
+    let symbols = session.context::<Symbols>().unwrap();
+    assert_eq!(symbols.0, ["a", "b"]);
+}
+
+#[test]
+fn undeclared_names_are_errors() {
+    let session = Session::new().with_context(Symbols::default());
+    let state = State::new("+a;b;").with_session(&session);
+    let result = program().parse(state);
+    assert!(matches!(result, ParseResult::Err(Error::Undefined, pos) if pos.offset == 4));
+}
+
+#[test]
+fn backtracking_undoes_context_changes() {
+    let session = Session::new().with_context(Symbols::default());
+    // This declares `a`, but then fails because there is no `!`.
+    let declare_then_bang = declare().and_then(|()| char_eq('!')).map(|_| ());
+    let parser = declare_then_bang
+        .or(declare().and_then(|()| char_eq(';')).map(|_| ()))
+        .map_fail(|((), ())| ());
+    let state = State::new("+a;").with_session(&session);
+    assert!(matches!(parser.parse(state), ParseResult::Ok((), _)));
+    // `a` was declared only once.
+    assert_eq!(session.context::<Symbols>().unwrap().0, ["a"]);
+
+    let state = State::new("+b;").with_session(&session);
+    let failing = declare().and_then(|()| char_eq('!'));
+    assert!(matches!(failing.parse(state), ParseResult::Fail(..)));
+    assert_eq!(session.context::<Symbols>().unwrap().0, ["a"]);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Depth(usize);
+
+#[test]
+fn local_context_is_restored() {
+    let session = Session::new().with_context(Depth(0));
+    let depth = || Parser::<Rc<Depth>, Error>::context().map(|d| d.0);
+    let parser = depth()
+        .local_context(|d: &mut Depth| d.0 += 1)
+        .and_then(move |inner| depth().map(move |outer| (inner, outer)));
+    let state = State::new("").with_session(&session);
+    assert!(matches!(parser.parse(state), ParseResult::Ok((1, 0), _)));
+}
+
+#[test]
+fn contexts_of_different_types_live_together() {
+    let session = Session::new()
+        .with_context(Depth(3))
+        .with_context(Symbols::default());
+    let parser = Parser::<(), Error>::set_context(Depth(7))
+        .and_then(|()| Parser::update_context(|s: &mut Symbols| s.0.push("x".into())));
+    let state = State::new("").with_session(&session);
+    assert!(matches!(parser.parse(state), ParseResult::Ok((), _)));
+    assert_eq!(*session.context::<Depth>().unwrap(), Depth(7));
+    assert_eq!(session.context::<Symbols>().unwrap().0, ["x"]);
+}
+
+/// A context that counts its copies.
+#[derive(Debug, Clone)]
+struct Counted {
+    _copies: Rc<()>,
+    count: usize,
+}
+
+#[test]
+fn updates_do_not_keep_old_copies() {
+    let text = "+".repeat(1000);
+    let copies = Rc::new(());
+    let session = Session::new().with_context(Counted {
+        _copies: copies.clone(),
+        count: 0,
+    });
+    let count = || Parser::<(), Error>::update_context(|c: &mut Counted| c.count += 1);
+    let counts = char_eq('+').and_then(move |_| count()).repeat_0::<()>();
+    let result = counts.parse(State::new(&text).with_session(&session));
+    assert!(matches!(result, ParseResult::Ok(v, _) if v.len() == 1000));
+    assert_eq!(session.context::<Counted>().unwrap().count, 1000);
+    assert!(Rc::strong_count(&copies) < 10);
+
+    // Changes are still undone when a parser around them fails.
+    let failing = counts.and_then(|_| char_eq('!'));
+    let result = failing.parse(State::new(&text).with_session(&session));
+    assert!(matches!(result, ParseResult::Fail(..)));
+    assert_eq!(session.context::<Counted>().unwrap().count, 1000);
+}
@@ -0,0 +1,84 @@
+use nessie_parse::{ParseResult, Parser, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Error {
+    Expected(&'static str),
+    Unexpected,
+}
+
+impl From<()> for Error {
+    fn from((): ()) -> Self {
+        Error::Unexpected
+    }
+}
+
+type P<'a, T> = Parser<'a, T, Error>;
+
+fn keyword<'a>(word: &'static str) -> P<'a, ()> {
+    Parser::expect_string(word).map_fail(|_| ())
+}
+
+fn name<'a>() -> P<'a, &'a str> {
+    Parser::letter().repeat_1().recognize().map_fail(|_| ())
+}
+
+#[derive(Debug, PartialEq)]
+enum Statement<'a> {
+    If(&'a str),
+    Name(&'a str),
+}
+
+/// `if (name)` or a `name`.
+fn statement<'a>() -> P<'a, Statement<'a>> {
+    let condition = || {
+        keyword("(")
+            .then_commit_with(|()| name(), |()| Error::Expected("condition"))
+            .then_commit(|name| keyword(")").map(move |()| name))
+    };
+    let if_ = keyword("if ").then_commit(move |()| condition().map(Statement::If));
+    if_.or(name().map(Statement::Name)).map_fail(|((), ())| ())
+}
+
+#[test]
+fn then_commit_does_not_backtrack() {
+    let result = statement().parse(State::new("if (x)"));
+    assert!(matches!(result, ParseResult::Ok(Statement::If("x"), _)));
+    let result = statement().parse(State::new("iffy"));
+    assert!(matches!(
+        result,
+        ParseResult::Ok(Statement::Name("iffy"), _)
+    ));
+
+    // Without committing, `or` would parse `if` as a name.
+    let result = statement().parse(State::new("if x"));
+    assert!(matches!(
+        result,
+        ParseResult::Err(Error::Unexpected, pos) if pos.offset == 3
+    ));
+    let result = statement().parse(State::new("if (1)"));
+    assert!(matches!(
+        result,
+        ParseResult::Err(Error::Expected("condition"), pos) if pos.offset == 5
+    ));
+    let result = statement().parse(State::new("if (x"));
+    assert!(matches!(
+        result,
+        ParseResult::Err(Error::Unexpected, pos) if pos.offset == 5
+    ));
+}
+
+#[test]
+fn cut_turns_failures_into_errors() {
+    let parser = name().cut::<()>();
+    let result = parser.parse(State::new("x"));
+    assert!(matches!(result, ParseResult::Ok("x", _)));
+    let result = parser.parse(State::new("1"));
+    assert!(matches!(result, ParseResult::Err(Error::Unexpected, _)));
+
+    let parser = name().cut_with::<()>(|()| Error::Expected("name"));
+    let result = parser.parse(State::new("1"));
+    assert!(matches!(
+        result,
+        ParseResult::Err(Error::Expected("name"), _)
+    ));
+}
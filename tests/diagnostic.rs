@@ -0,0 +1,140 @@
+use indoc::indoc;
+use nessie_parse::{Diagnostic, Label, ParseResult, Parser, Pos, Report, Severity, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Error {
+    LetterAfterNumber,
+    Unclosed(Pos),
+}
+
+impl Diagnostic for Error {
+    fn message(&self) -> String {
+        match self {
+            Error::LetterAfterNumber => "a number can't be followed by a letter".to_string(),
+            Error::Unclosed(_) => "unclosed parenthesis".to_string(),
+        }
+    }
+
+    fn code(&self) -> Option<String> {
+        match self {
+            Error::LetterAfterNumber => Some("E001".to_string()),
+            Error::Unclosed(_) => None,
+        }
+    }
+
+    fn label(&self) -> Option<String> {
+        match self {
+            Error::LetterAfterNumber => Some("this letter".to_string()),
+            Error::Unclosed(_) => Some("expected `)` here".to_string()),
+        }
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        match self {
+            Error::LetterAfterNumber => vec![],
+            Error::Unclosed(open) => vec![Label::new(*open, "opened here")],
+        }
+    }
+
+    fn notes(&self) -> Vec<String> {
+        match self {
+            Error::LetterAfterNumber => vec!["add a space between them".to_string()],
+            Error::Unclosed(_) => vec![],
+        }
+    }
+}
+
+fn number<'a>() -> Parser<'a, &'a str, Error> {
+    Parser::digit()
+        .repeat_1()
+        .recognize()
+        .map_fail(|_| ())
+        .and_then(|digits| {
+            Parser::not::<()>(Parser::letter())
+                .or_err(Error::LetterAfterNumber)
+                .map(move |_| digits)
+        })
+}
+
+#[test]
+fn renders_a_parse_error() {
+    let source = "let x = 12ab;";
+    let result = Parser::<char, Error, _>::expect_string("let x = ")
+        .map_fail(|_| ())
+        .and_then(|()| number())
+        .parse(source.into());
+    assert!(matches!(result, ParseResult::Err(..)));
+    let report = result.report(source).unwrap().with_file_name("main.x");
+    assert_eq!(
+        report.to_string(),
+        indoc! {"
+            error[E001]: a number can't be followed by a letter
+             --> main.x:1:11
+              |
+            1 | let x = 12ab;
+              |           ^ this letter
+              |
+              = note: add a space between them
+        "}
+    );
+}
+
+#[test]
+fn renders_secondary_labels_and_spans() {
+    let source = "f(1,\n  2,\n  3;";
+    let open = Pos::start().advance('f');
+    let end = Pos {
+        offset: 13,
+        row: 3,
+        col: 4,
+    };
+    let error = Error::Unclosed(open);
+    let report = Report::new(source, Span::new(end, end.advance(';')), &error);
+    assert_eq!(
+        report.to_string(),
+        indoc! {"
+            error: unclosed parenthesis
+             --> 3:4
+              |
+            1 | f(1,
+              |  - opened here
+            ...
+            3 |   3;
+              |    ^ expected `)` here
+        "}
+    );
+}
+
+struct Unused;
+
+impl Diagnostic for Unused {
+    fn message(&self) -> String {
+        "unused variable".to_string()
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+}
+
+#[test]
+fn underlines_whole_span_with_color() {
+    let source = "let שלום = 1;";
+    let start = Pos::start()
+        .advance('l')
+        .advance('e')
+        .advance('t')
+        .advance(' ');
+    let end = "שלום".chars().fold(start, Pos::advance);
+    let report = Report::new(source, Span::new(start, end), &Unused).with_color(true);
+    let rendered = report.to_string();
+    assert!(rendered.starts_with("\x1b[1;33mwarning\x1b[0m"));
+    assert!(rendered.contains("^^^^"));
+    assert!(!rendered.contains("^^^^^"));
+}
+
+#[test]
+fn ok_results_have_no_report() {
+    let result = number().parse("12".into());
+    assert!(result.report("12").is_none());
+}
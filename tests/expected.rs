@@ -0,0 +1,86 @@
+use nessie_parse::{one_of, Expected, ParseResult, Parser};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+fn identifier<'a>() -> Parser<'a, &'a str, Error, Expected> {
+    Parser::letter().repeat_1().recognize().label("identifier")
+}
+
+fn number<'a>() -> Parser<'a, &'a str, Error, Expected> {
+    Parser::digit().repeat_1().recognize().label("number")
+}
+
+fn string<'a>() -> Parser<'a, &'a str, Error, Expected> {
+    Parser::char_eq('"')
+        .and_then(|_| Parser::letter().repeat_0().map_fail(|()| ()))
+        .and_then(|_| Parser::char_eq('"'))
+        .recognize()
+        .label("string")
+}
+
+fn fail_of<T: std::fmt::Debug>(result: ParseResult<T, Error, Expected>) -> Expected {
+    match result {
+        ParseResult::Fail(expected, _) => expected,
+        other => panic!("expected Fail, got {other:?}"),
+    }
+}
+
+#[test]
+fn or_merges_labels() {
+    let parser = identifier().or::<_, Expected>(number());
+    let expected = fail_of(parser.parse("+1".into()));
+    assert_eq!(expected.labels, ["identifier", "number"]);
+    assert_eq!(expected.pos.offset, 0);
+    assert_eq!(
+        expected.to_string(),
+        "expected identifier or number, found '+'"
+    );
+}
+
+#[test]
+fn one_of_merges_labels() {
+    let parser: Parser<_, _, Expected> = one_of![identifier(), number(), string()];
+    let expected = fail_of(parser.parse("".into()));
+    assert_eq!(
+        expected.to_string(),
+        "expected identifier, number or string, found end of input"
+    );
+}
+
+#[test]
+fn furthest_failure_wins() {
+    let assignment = identifier()
+        .and_then(|_| Parser::<_, _, ()>::char_eq('=').label("'='"))
+        .and_then(|_| number().or::<_, Expected>(string()));
+    let parser = assignment.or::<_, Expected>(number());
+    let expected = fail_of(parser.parse("x=+".into()));
+    assert_eq!(expected.pos.offset, 2);
+    assert_eq!(expected.labels, ["number", "string"]);
+
+    let expected = fail_of(parser.parse("x+".into()));
+    assert_eq!(expected.pos.offset, 1);
+    assert_eq!(expected.to_string(), "expected '=', found '+'");
+}
+
+#[test]
+fn label_keeps_a_failure_that_got_further() {
+    let expected = fail_of(string().parse("\"ab".into()));
+    assert_eq!(expected.pos.offset, 3);
+    assert_eq!(expected.to_string(), "expected string, found end of input");
+
+    let expected = fail_of(string().parse("ab".into()));
+    assert_eq!(expected.pos.offset, 0);
+    assert_eq!(expected.to_string(), "expected string, found 'a'");
+}
+
+#[test]
+fn merge_prefers_further_position() {
+    let near = fail_of(identifier().parse("1".into()));
+    let far = Expected {
+        pos: near.pos.advance('1'),
+        ..Expected::default()
+    };
+    assert_eq!(near.clone().merge(far.clone()), far);
+    assert_eq!(far.clone().merge(near), far);
+}
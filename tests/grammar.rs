@@ -0,0 +1,182 @@
+use nessie_parse::{ForwardDecl, Grammar, ParseResult, Parser, State};
+
+use indoc::indoc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+type P<'a, T> = Parser<'a, T, Error>;
+
+fn char_eq<'a>(ch: char) -> P<'a, char> {
+    Parser::char_eq(ch)
+}
+
+fn word<'a>() -> P<'a, String> {
+    Parser::letter()
+        .repeat_1()
+        .map(String::from_iter)
+        .map_fail(|_| ())
+        .rule("word")
+}
+
+/// `list = item, { ",", item }`, where an item is a word or a list in
+/// parentheses.
+fn list<'a>() -> P<'a, Vec<String>> {
+    let list = ForwardDecl::new();
+    let group = {
+        let list = list.parser();
+        let grammar = Grammar::Seq(vec![
+            char_eq('(').describe(),
+            list.describe(),
+            char_eq(')').describe(),
+        ]);
+        char_eq('(')
+            .and_then(move |_| list.clone())
+            .and_then(|items: Vec<String>| char_eq(')').map(move |_| items.join(" ")))
+            .with_grammar(grammar)
+            .rule("group")
+    };
+    let item = word().or(group).map_fail(|((), ())| ()).rule("item");
+    list.define(item.sep_by_1(char_eq(',')).rule("list"))
+}
+
+#[test]
+fn describes_as_ebnf() {
+    let list = list();
+    let result = list.parse(State::new("a,(b,c),d"));
+    assert!(matches!(result, ParseResult::Ok(ref items, _) if items == &["a", "b c", "d"]));
+    assert_eq!(
+        list.describe().to_ebnf(),
+        indoc! {r#"
+            list = item, { ",", item } ;
+            item = word | group ;
+            word = ? letter ?, { ? letter ? } ;
+            group = "(", list, ")" ;
+        "#}
+    );
+}
+
+#[test]
+fn describes_combinators() {
+    let sign = char_eq('-').maybe::<()>();
+    assert_eq!(sign.describe().to_ebnf(), "start = [ \"-\" ] ;\n");
+
+    let digits = Parser::digit().repeat_range(2..=3).map_fail(|_| ());
+    let number = P::one_of([
+        digits,
+        Parser::expect_string("none")
+            .map(|()| vec![])
+            .map_fail(|_| ()),
+    ]);
+    assert_eq!(
+        number.map_fail(|()| ()).describe().to_ebnf(),
+        "start = 2 * ? digit ?, [ ? digit ? ] | \"none\" ;\n"
+    );
+
+    // A closure can't be looked into.
+    let pair = char_eq('a').and_then(|_| char_eq('b')).with_name("pair");
+    assert_eq!(pair.describe(), Grammar::Terminal("pair".to_string()));
+}
+
+#[test]
+fn renaming_a_rule_makes_a_new_rule() {
+    let ident = word().rule("ident");
+    let label = ident.clone().rule("label");
+    assert_eq!(
+        ident.describe().to_ebnf(),
+        indoc! {r#"
+            ident = word ;
+            word = ? letter ?, { ? letter ? } ;
+        "#}
+    );
+    assert_eq!(
+        label.describe().to_ebnf(),
+        indoc! {r#"
+            label = ident ;
+            ident = word ;
+            word = ? letter ?, { ? letter ? } ;
+        "#}
+    );
+}
+
+#[test]
+fn describes_repetitions_with_factors() {
+    let repeat = |min, max| {
+        let inner = Grammar::Alt(vec![
+            Grammar::Literal("a".to_string()),
+            Grammar::Literal("b".to_string()),
+        ]);
+        let inner = Box::new(inner);
+        Grammar::Repeat { inner, min, max }.to_ebnf()
+    };
+    assert_eq!(repeat(0, Some(20)), "start = 20 * [ \"a\" | \"b\" ] ;\n");
+    assert_eq!(
+        repeat(1, Some(1_000_000)),
+        "start = ( \"a\" | \"b\" ), 999999 * [ \"a\" | \"b\" ] ;\n"
+    );
+    assert_eq!(
+        repeat(3, None),
+        "start = 3 * ( \"a\" | \"b\" ), { \"a\" | \"b\" } ;\n"
+    );
+    assert_eq!(repeat(2, Some(2)), "start = 2 * ( \"a\" | \"b\" ) ;\n");
+
+    let digits = Parser::<_, Error, _>::digit()
+        .repeat_range(0..=usize::MAX)
+        .map_fail(|_| ());
+    let pair: P<_> = digits.clone().repeat_range(2..=2).map_fail(|_| ());
+    assert_eq!(
+        pair.describe().to_ebnf(),
+        format!("start = 2 * ( {} * [ ? digit ? ] ) ;\n", usize::MAX)
+    );
+}
+
+#[test]
+fn escapes_literals() {
+    let literal = |text: &str| Grammar::Literal(text.to_string()).to_ebnf();
+    assert_eq!(literal("a'b"), "start = \"a'b\" ;\n");
+    assert_eq!(literal("a\"b"), "start = 'a\"b' ;\n");
+    // Text that an EBNF string can't hold is escaped in a special sequence.
+    assert_eq!(literal("'\"").trim_end(), r#"start = ? "'\"" ? ;"#);
+    assert_eq!(literal("\n").trim_end(), r#"start = ? "\n" ? ;"#);
+    assert_eq!(literal("?\t").trim_end(), r#"start = ? "\u{3f}\t" ? ;"#);
+}
+
+#[test]
+fn describes_as_dot() {
+    assert_eq!(
+        list().describe().to_dot(),
+        indoc! {r#"
+            digraph grammar {
+              rule0 [label="list", shape=box, style=bold];
+              n0 [label="seq", shape=circle];
+              rule1 [label="item", shape=box, style=bold];
+              n0 -> rule1 [label=1];
+              n1 [label="0..", shape=circle];
+              n2 [label="seq", shape=circle];
+              n3 [label="\",\"", shape=ellipse];
+              n2 -> n3 [label=1];
+              n2 -> rule1 [label=2];
+              n1 -> n2;
+              n0 -> n1 [label=2];
+              rule0 -> n0;
+              n4 [label="alt", shape=diamond];
+              rule2 [label="word", shape=box, style=bold];
+              n4 -> rule2;
+              rule3 [label="group", shape=box, style=bold];
+              n4 -> rule3;
+              rule1 -> n4;
+              n5 [label="1..", shape=circle];
+              n6 [label="letter", shape=plaintext];
+              n5 -> n6;
+              rule2 -> n5;
+              n7 [label="seq", shape=circle];
+              n8 [label="\"(\"", shape=ellipse];
+              n7 -> n8 [label=1];
+              n7 -> rule0 [label=2];
+              n9 [label="\")\"", shape=ellipse];
+              n7 -> n9 [label=3];
+              rule3 -> n7;
+            }
+        "#}
+    );
+}
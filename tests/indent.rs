@@ -0,0 +1,130 @@
+use indoc::indoc;
+use nessie_parse::{BadIndent, ParseResult, Parser, Pos};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+fn word<'a>() -> Parser<'a, &'a str, Error> {
+    Parser::letter().repeat_1().recognize().map_fail(|_| ())
+}
+
+/// A section is a word, followed by a block of words indented under it.
+fn section<'a>() -> Parser<'a, (&'a str, Vec<&'a str>), Error> {
+    Parser::checkpoint_indent().and_then(|start: Pos| {
+        word().and_then(move |title| {
+            Parser::skip_whitespace()
+                .and_then(move |()| word().block().indented(start.col))
+                .map(move |items| (title, items))
+                .map_fail(|_| ())
+        })
+    })
+}
+
+fn sections<'a>() -> Parser<'a, Vec<(&'a str, Vec<&'a str>)>, Error> {
+    section().block()
+}
+
+#[test]
+fn parses_blocks() {
+    let text = indoc! {"
+        fruits
+          apple
+          banana
+        veggies
+            carrot
+    "};
+    let ParseResult::Ok(result, _) = sections().parse(text.into()) else {
+        panic!("expected Ok");
+    };
+    assert_eq!(
+        result,
+        [
+            ("fruits", vec!["apple", "banana"]),
+            ("veggies", vec!["carrot"]),
+        ]
+    );
+}
+
+#[test]
+fn misaligned_item_ends_the_block() {
+    let text = indoc! {"
+        fruits
+          apple
+           banana
+    "};
+    let ParseResult::Ok(result, pos) = sections().parse(text.into()) else {
+        panic!("expected Ok");
+    };
+    assert_eq!(result, [("fruits", vec!["apple"])]);
+    assert_eq!(pos.row, 2);
+}
+
+#[test]
+fn block_must_be_indented() {
+    let text = "fruits\napple";
+    let result = section().parse(text.into());
+    assert!(matches!(result, ParseResult::Fail(..)));
+
+    let result = Parser::skip_whitespace()
+        .and_then(|()| word().indented(2))
+        .parse(" x".into());
+    assert!(matches!(
+        result,
+        ParseResult::Fail(BadIndent::NotIndented { col: 2, found }, _) if found.col == 2
+    ));
+}
+
+#[test]
+fn aligned_with_and_same_line() {
+    let result = word().aligned_with(1).parse("abc".into());
+    assert!(matches!(result, ParseResult::Ok("abc", _)));
+    let result = Parser::skip_whitespace()
+        .and_then(|()| word().aligned_with(1))
+        .parse("  abc".into());
+    assert!(matches!(
+        result,
+        ParseResult::Fail(BadIndent::NotAligned { col: 1, .. }, _)
+    ));
+    let result = Parser::skip_whitespace()
+        .and_then(|()| word().same_line(1))
+        .parse("\nabc".into());
+    assert!(matches!(
+        result,
+        ParseResult::Fail(BadIndent::NotOnRow { row: 1, .. }, _)
+    ));
+    let result = word().same_line(1).parse("1".into());
+    assert!(matches!(result, ParseResult::Fail(BadIndent::Fail(()), _)));
+}
+
+/// A command and its arguments, where arguments may continue on more
+/// indented lines.
+fn command<'a>() -> Parser<'a, Vec<&'a str>, Error> {
+    Parser::line_fold(|space| {
+        let arg = space.map_fail(|_| ()).and_then(|()| word());
+        word().and_then(move |first| {
+            arg.clone()
+                .repeat_0()
+                .map(move |rest| [vec![first], rest].concat())
+        })
+    })
+}
+
+#[test]
+fn line_fold_continues_on_indented_lines() {
+    let text = indoc! {"
+        run this
+          and that
+        next
+    "};
+    let ParseResult::Ok(words, pos) = command().parse(text.into()) else {
+        panic!("expected Ok");
+    };
+    assert_eq!(words, ["run", "this", "and", "that"]);
+    assert_eq!(pos.row, 2);
+}
+
+#[test]
+fn block_stops_after_an_item_that_consumed_nothing() {
+    let result = Parser::<(), Error, ()>::ret(()).block().parse("x".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v.len() == 1 && pos.offset == 0));
+}
@@ -0,0 +1,106 @@
+use nessie_parse::{one_of, ColumnMode, Input, ParseResult, Parser, Pos, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+fn hex_digit<'a>() -> Parser<'a, u32, Error, (), [u8]> {
+    Parser::<_, Error, (), [u8]>::satisfy(|b| b.is_ascii_hexdigit())
+        .map(|b| char::from(*b).to_digit(16).unwrap())
+}
+
+/// A hex number, terminated by a zero byte.
+fn hex_number<'a>() -> Parser<'a, u32, Error, (), [u8]> {
+    hex_digit()
+        .repeat_1()
+        .map(|digits| digits.into_iter().fold(0, |n, d| n * 16 + d))
+        .and_then(|n| Parser::item_eq(&0).map(move |_| n))
+}
+
+#[test]
+fn parses_bytes() {
+    let bytes = b"1f\0rest";
+    let result = hex_number().parse(bytes.as_slice().into());
+    assert!(matches!(result, ParseResult::Ok(31, pos) if pos.offset == 3));
+
+    let result = hex_number().parse(b"1g\0".as_slice().into());
+    assert!(matches!(result, ParseResult::Fail((), pos) if pos.offset == 1));
+}
+
+#[test]
+fn recognize_returns_a_sub_slice() {
+    let letters = Parser::<_, Error, (), [u8]>::satisfy(|b| b.is_ascii_alphabetic())
+        .repeat_1()
+        .recognize();
+    let result = letters.parse(b"abc123".as_slice().into());
+    assert!(matches!(result, ParseResult::Ok(b"abc", _)));
+}
+
+#[test]
+fn same_vocabulary_over_any_items() {
+    let item = |n| Parser::<_, Error, (), [i32]>::item_eq(n);
+    let number = one_of![item(&1).map(|_| "one"), item(&2).map(|_| "two")].map_fail(|()| ());
+    let numbers = number.repeat_0::<()>();
+    let result = numbers.parse([1, 2, 2, 3].as_slice().into());
+    assert!(
+        matches!(result, ParseResult::Ok(v, pos) if v == ["one", "two", "two"] && pos.offset == 3)
+    );
+
+    let all = numbers.and_then(|v| Parser::eof().map(move |()| v.len()).map_fail(|_| ()));
+    assert!(matches!(
+        all.parse([2, 1].as_slice().into()),
+        ParseResult::Ok(2, _)
+    ));
+    assert!(matches!(
+        all.parse([2, 3].as_slice().into()),
+        ParseResult::Fail(..)
+    ));
+}
+
+/// Text where letters are all lowercase, but slices keep their case.
+struct Caseless<'s>(&'s str);
+
+impl Input for Caseless<'_> {
+    type Item<'a>
+        = char
+    where
+        Self: 'a;
+    type Slice<'a>
+        = &'a str
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn slice(&self, start: usize, end: usize) -> &str {
+        &self.0[start..end]
+    }
+
+    fn next_item(&self, pos: Pos, column_mode: ColumnMode) -> Option<(char, Pos)> {
+        let (ch, new_pos) = self.0.next_item(pos, column_mode)?;
+        Some((ch.to_ascii_lowercase(), new_pos))
+    }
+}
+
+/// Works over any input of characters.
+fn keyword<'a, I>(word: &'static str) -> Parser<'a, (), Error, (), I>
+where
+    I: Input<Item<'a> = char> + ?Sized + 'a,
+{
+    word.chars().fold(Parser::ret(()), |parser, ch| {
+        parser.and_then(move |()| Parser::item_eq(ch).map(|_| ()))
+    })
+}
+
+#[test]
+fn custom_streams() {
+    let result = keyword("select").parse("select".into());
+    assert!(matches!(result, ParseResult::Ok((), _)));
+    let result = keyword("select").parse("SELECT".into());
+    assert!(matches!(result, ParseResult::Fail(..)));
+
+    let text = Caseless("SeLeCt *");
+    let result = keyword("select").recognize().parse(State::of(&text));
+    assert!(matches!(result, ParseResult::Ok("SeLeCt", pos) if pos.col == 7));
+}
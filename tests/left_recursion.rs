@@ -0,0 +1,67 @@
+use nessie_parse::{ParseResult, Parser, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Error {
+    MissingParen,
+}
+
+type P<'a, T> = Parser<'a, T, Error>;
+
+fn number<'a>() -> P<'a, i64> {
+    Parser::digit()
+        .repeat_1()
+        .recognize()
+        .map(|digits: &str| digits.parse().unwrap())
+        .map_fail(|_| ())
+}
+
+/// `expr = expr '-' atom | atom`, and `atom = number | '(' expr ')'`.
+fn expr<'a>() -> P<'a, i64> {
+    P::left_recursive(|this| {
+        let parens = P::char_eq('(').and_then({
+            let this = this.clone();
+            move |_| {
+                this.clone()
+                    .and_then(|value| P::char_eq(')').map(move |_| value))
+                    .or_err(Error::MissingParen)
+            }
+        });
+        let atom = number().or(parens).map_fail(|((), ())| ());
+        let minus = this.and_then({
+            let atom = atom.clone();
+            move |left| {
+                let atom = atom.clone();
+                P::char_eq('-').and_then(move |_| atom.clone().map(move |right| left - right))
+            }
+        });
+        minus.or(atom).map_fail(|((), ())| ())
+    })
+}
+
+#[test]
+fn left_recursion_is_left_associative() {
+    let result = expr().parse(State::new("7-2-1"));
+    assert!(matches!(result, ParseResult::Ok(4, pos) if pos.offset == 5));
+    let result = expr().parse(State::new("10"));
+    assert!(matches!(result, ParseResult::Ok(10, _)));
+}
+
+#[test]
+fn left_recursion_stops_at_the_longest_match() {
+    let result = expr().parse(State::new("7-2-x"));
+    assert!(matches!(result, ParseResult::Ok(5, pos) if pos.offset == 3));
+    let result = expr().parse(State::new("-1"));
+    assert!(matches!(result, ParseResult::Fail((), pos) if pos.offset == 0));
+}
+
+#[test]
+fn left_recursion_nests() {
+    let result = expr().parse(State::new("10-(5-(3-1))-2"));
+    assert!(matches!(result, ParseResult::Ok(5, _)));
+}
+
+#[test]
+fn left_recursion_passes_errors_on() {
+    let result = expr().parse(State::new("1-(2-3"));
+    assert!(matches!(result, ParseResult::Err(Error::MissingParen, _)));
+}
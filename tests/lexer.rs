@@ -0,0 +1,138 @@
+use nessie_parse::{Assoc, Lexer, NoToken, ParseResult, Parser, State, Token};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tok<'a> {
+    Let,
+    Ident(&'a str),
+    Number(i64),
+    Plus,
+    Star,
+    Equals,
+}
+
+fn lexer<'a>() -> Parser<'a, Vec<Token<Tok<'a>>>, Error, NoToken> {
+    let ident = Parser::letter().repeat_1().recognize().map(Tok::Ident);
+    let number = Parser::digit()
+        .repeat_1()
+        .recognize()
+        .map(|digits: &str| Tok::Number(digits.parse().unwrap()));
+    let comment = Parser::<char, Error, _>::expect_string("#")
+        .and_then(|()| Parser::char().filter(|&c| c != '\n').repeat_0());
+    Lexer::new()
+        .literal("let", Tok::Let)
+        .token(ident)
+        .token(number)
+        .literal("+", Tok::Plus)
+        .literal("*", Tok::Star)
+        .literal("=", Tok::Equals)
+        .skip(Parser::whitespace())
+        .skip(comment)
+        .build()
+}
+
+fn lex(text: &str) -> Vec<Token<Tok<'_>>> {
+    let ParseResult::Ok(tokens, _) = lexer().parse(text.into()) else {
+        panic!("expected Ok");
+    };
+    tokens
+}
+
+fn kinds<'a>(tokens: &[Token<Tok<'a>>]) -> Vec<Tok<'a>> {
+    tokens.iter().map(|token| token.kind).collect()
+}
+
+#[test]
+fn lexes_with_spans() {
+    let text = "let x = 12 # a comment\n+ 3";
+    let tokens = lex(text);
+    assert_eq!(
+        kinds(&tokens),
+        [
+            Tok::Let,
+            Tok::Ident("x"),
+            Tok::Equals,
+            Tok::Number(12),
+            Tok::Plus,
+            Tok::Number(3),
+        ]
+    );
+    assert_eq!(tokens[3].text(text), "12");
+    assert_eq!((tokens[3].span.start.col, tokens[3].span.end.col), (9, 11));
+    assert_eq!((tokens[4].span.start.row, tokens[4].span.start.col), (2, 1));
+}
+
+#[test]
+fn longest_match_then_first_rule() {
+    let tokens = lex("letter let");
+    assert_eq!(kinds(&tokens), [Tok::Ident("letter"), Tok::Let]);
+}
+
+#[test]
+fn no_token_fails_where_it_got_stuck() {
+    let result = lexer().parse("1 $ 2".into());
+    assert!(matches!(result, ParseResult::Fail(NoToken, pos) if pos.offset == 2));
+}
+
+type TokenParser<'a, T> = Parser<'a, T, Error, (), [Token<Tok<'a>>]>;
+
+fn tok<'a>(kind: Tok<'a>) -> TokenParser<'a, ()> {
+    Parser::token_eq(kind).map(|_| ())
+}
+
+fn expression<'a>() -> TokenParser<'a, i64> {
+    let number = Parser::token_with(|kind| match kind {
+        Tok::Number(n) => Some(*n),
+        _ => None,
+    });
+    number
+        .expression()
+        .infix(tok(Tok::Plus), 1, Assoc::Left, |a, (), b| a + b)
+        .infix(tok(Tok::Star), 2, Assoc::Left, |a, (), b| a * b)
+        .build()
+}
+
+/// `let name = expression`
+fn statement<'a>() -> TokenParser<'a, (&'a str, i64)> {
+    let ident = Parser::token_with(|kind| match kind {
+        Tok::Ident(name) => Some(*name),
+        _ => None,
+    });
+    tok(Tok::Let).and_then(move |()| {
+        ident.clone().and_then(|name| {
+            tok(Tok::Equals)
+                .and_then(|()| expression())
+                .map(move |value| (name, value))
+        })
+    })
+}
+
+#[test]
+fn combinators_run_over_tokens() {
+    let tokens = lex("let x = 1 + 2 * 3");
+    let result = statement().parse(State::of(tokens.as_slice()));
+    assert!(matches!(result, ParseResult::Ok(("x", 7), pos) if pos.offset == 8));
+}
+
+#[test]
+fn token_positions_map_back_to_source() {
+    let text = "let x\n  + 1";
+    let tokens = lex(text);
+    let ParseResult::Fail((), pos) = statement().parse(State::of(tokens.as_slice())) else {
+        panic!("expected Fail");
+    };
+    // The failure is at the `+`, which is the third token.
+    assert_eq!(pos.offset, 2);
+    let span = Token::span_at(&tokens, pos);
+    assert_eq!((span.start.row, span.start.col), (2, 3));
+
+    // And at the end of the tokens, it is right after the last one.
+    let tokens = lex("let x =");
+    let ParseResult::Fail((), pos) = statement().parse(State::of(tokens.as_slice())) else {
+        panic!("expected Fail");
+    };
+    let span = Token::span_at(&tokens, pos);
+    assert_eq!((span.start.col, span.len()), (8, 0));
+}
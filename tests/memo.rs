@@ -0,0 +1,161 @@
+use nessie_parse::{ParseResult, Parser, Session, State};
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+type P<'a, T> = Parser<'a, T, Error>;
+
+/// `x`, followed by `depth` signs. Every level tries three alternatives that
+/// all start with the level below it, so without memoization the `x` is
+/// parsed `3^depth` times.
+fn signs<'a>(depth: usize, memoize: bool, calls: Rc<Cell<usize>>) -> P<'a, usize> {
+    let mut parser = P::ret(()).and_then(move |()| {
+        calls.set(calls.get() + 1);
+        P::char_eq('x').map(|_| 0)
+    });
+    for level in 1..=depth {
+        if memoize {
+            parser = parser.memoize();
+        }
+        let plus = parser
+            .clone()
+            .and_then(|n| P::char_eq('+').map(move |_| n + 1));
+        let minus = parser
+            .clone()
+            .and_then(|n| P::char_eq('-').map(move |_| n + 1));
+        // A short name, since the default one has the names of all of the
+        // alternatives, which is exponential too.
+        parser = P::one_of([plus, minus, parser])
+            .map_fail(|()| ())
+            .with_name(format!("signs{level}"));
+    }
+    parser
+}
+
+#[test]
+fn memoize_parses_once_per_offset() {
+    let calls = Rc::new(Cell::new(0));
+    let parser = signs(8, false, calls.clone());
+    let result = parser.parse(State::new("x"));
+    assert!(matches!(result, ParseResult::Ok(0, _)));
+    assert_eq!(calls.get(), 3usize.pow(8));
+
+    let calls = Rc::new(Cell::new(0));
+    let session = Session::new();
+    let parser = signs(8, true, calls.clone());
+    let result = parser.parse(State::new("x+-+").with_session(&session));
+    assert!(matches!(result, ParseResult::Ok(3, pos) if pos.offset == 4));
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn memoize_without_a_session_parses_every_time() {
+    let calls = Rc::new(Cell::new(0));
+    let parser = signs(4, true, calls.clone());
+    let result = parser.parse(State::new("x"));
+    assert!(matches!(result, ParseResult::Ok(0, _)));
+    assert_eq!(calls.get(), 3usize.pow(4));
+}
+
+#[test]
+fn a_new_session_starts_over() {
+    let (first, second) = (Session::new(), Session::new());
+    let calls = Rc::new(Cell::new(0));
+    let parser = signs(4, true, calls.clone());
+    let result = parser.parse(State::new("x+").with_session(&first));
+    assert!(matches!(result, ParseResult::Ok(1, _)));
+    // Remembering the `x` from the first run would make this succeed.
+    let result = parser.parse(State::new("y").with_session(&second));
+    assert!(matches!(result, ParseResult::Fail((), _)));
+    assert_eq!(calls.get(), 2);
+}
+
+/// Not `Clone`.
+#[derive(Debug, PartialEq)]
+struct Word(String);
+
+#[test]
+fn memoize_rc_shares_the_output() {
+    let session = Session::new();
+    let word = Parser::letter()
+        .repeat_1()
+        .recognize()
+        .map(|text| Word(text.to_string()))
+        .map_fail(|_| ())
+        .memoize_rc();
+    let first = Rc::new(RefCell::new(None));
+    let keep = first.clone();
+    let twice = word
+        .clone()
+        .and_then(move |word| {
+            *keep.borrow_mut() = Some(word);
+            P::char_eq('!')
+        })
+        .map(|_| None)
+        .or(word.map(Some))
+        .map_fail(|((), ())| ());
+    let ParseResult::Ok(Some(second), _) = twice.parse(State::new("nessie").with_session(&session))
+    else {
+        panic!("expected Ok");
+    };
+    assert_eq!(*second, Word("nessie".to_string()));
+    assert!(Rc::ptr_eq(first.borrow().as_ref().unwrap(), &second));
+}
+
+fn word<'a>() -> P<'a, &'a str> {
+    Parser::letter()
+        .repeat_1()
+        .recognize()
+        .map_fail(|_| ())
+        .memoize()
+}
+
+#[test]
+fn a_session_can_parse_many_texts() {
+    let session = Session::new();
+    let word = word();
+    let result = word.parse(State::new("abc").with_session(&session));
+    assert!(matches!(result, ParseResult::Ok("abc", pos) if pos.offset == 3));
+    let result = word.parse(State::new("xy").with_session(&session));
+    assert!(matches!(result, ParseResult::Ok("xy", pos) if pos.offset == 2));
+
+    // A shorter part of the same text, in the same run.
+    let word = self::word();
+    let both = P::from_fn(move |state| {
+        let start = State {
+            text: &state.text[..1],
+            ..state
+        };
+        match (word.parse(state), word.parse(start)) {
+            (ParseResult::Ok(all, _), ParseResult::Ok(start, pos)) => {
+                ParseResult::Ok((all, start), pos)
+            }
+            _ => ParseResult::Fail((), state.pos),
+        }
+    });
+    let result = both.parse(State::new("abc").with_session(&session));
+    assert!(matches!(result, ParseResult::Ok(("abc", "a"), _)));
+}
+
+#[test]
+fn recorded_errors_are_not_remembered() {
+    let session = Session::new();
+    let calls = Rc::new(Cell::new(0));
+    let counter = calls.clone();
+    let parser = P::ret(())
+        .and_then(move |()| {
+            counter.set(counter.get() + 1);
+            P::from_fn(|state| {
+                state.session.unwrap().push_error(Error, state.pos);
+                ParseResult::Ok((), state.pos)
+            })
+        })
+        .memoize();
+    let twice = parser.clone().and_then(move |()| parser.clone());
+    twice.parse(State::new("").with_session(&session));
+    assert_eq!(calls.get(), 2);
+    assert_eq!(session.errors::<Error>().len(), 2);
+}
@@ -0,0 +1,69 @@
+use nessie_parse::{parse, ParseResult, Parser, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Error {
+    UnclosedPair,
+}
+
+type P<'a, T> = Parser<'a, T, Error>;
+
+fn char_eq<'a>(ch: char) -> P<'a, char> {
+    Parser::char_eq(ch)
+}
+
+fn word<'a>() -> P<'a, String> {
+    Parser::letter()
+        .repeat_1()
+        .map(String::from_iter)
+        .map_fail(|_| ())
+}
+
+/// `(a,b)`, where the closing parenthesis is required.
+fn pair<'a>() -> P<'a, (String, String)> {
+    parse! {
+        char_eq('(');
+        let a = word();
+        let _ = char_eq(',');
+        let b = word();
+        char_eq(')').or_err(Error::UnclosedPair);
+        ret (a, b)
+    }
+}
+
+#[test]
+fn parse_binds_values() {
+    let result = pair().parse(State::new("(ab,c)!"));
+    match result {
+        ParseResult::Ok((a, b), pos) => {
+            assert_eq!((a.as_str(), b.as_str()), ("ab", "c"));
+            assert_eq!(pos.offset, 6);
+        }
+        _ => panic!("expected Ok, got {result:?}"),
+    }
+}
+
+#[test]
+fn parse_propagates_fail_and_err() {
+    let result = pair().parse(State::new("(ab;c)"));
+    assert!(matches!(result, ParseResult::Fail((), pos) if pos.offset == 4));
+
+    let result = pair().parse(State::new("(ab,c"));
+    assert!(matches!(result, ParseResult::Err(Error::UnclosedPair, pos) if pos.offset == 5));
+}
+
+#[test]
+fn parse_uses_bound_values() {
+    // A letter, and then the same letter again.
+    let twice: P<char> = parse! {
+        let first = Parser::letter().map_fail(|_| ());
+        char_eq(first)
+    };
+    assert!(matches!(
+        twice.parse(State::new("aa")),
+        ParseResult::Ok('a', _)
+    ));
+    assert!(matches!(
+        twice.parse(State::new("ab")),
+        ParseResult::Fail(..)
+    ));
+}
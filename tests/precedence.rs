@@ -0,0 +1,86 @@
+use nessie_parse::{Assoc, ParseResult, Parser};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+fn number<'a>() -> Parser<'a, i64, Error> {
+    Parser::digit()
+        .repeat_1()
+        .map(|digits| digits.into_iter().collect::<String>().parse().unwrap())
+        .map_fail(|_| ())
+}
+
+fn op<'a>(ch: char) -> Parser<'a, char, Error> {
+    Parser::char_eq(ch)
+}
+
+fn atom<'a>() -> Parser<'a, String, Error> {
+    let parens = op('(').and_then(|_| expr().and_then(|e| op(')').map(move |_| e.clone())));
+    number()
+        .map(|n| n.to_string())
+        .or(parens)
+        .map_fail(|((), ())| ())
+}
+
+/// Parses an expression into a fully parenthesized string.
+fn expr<'a>() -> Parser<'a, String, Error> {
+    atom()
+        .expression()
+        .prefix(op('-'), 3, |_, x| format!("(-{x})"))
+        .infix(op('+'), 1, Assoc::Left, |x, _, y| format!("({x} + {y})"))
+        .infix(op('-'), 1, Assoc::Left, |x, _, y| format!("({x} - {y})"))
+        .infix(op('*'), 2, Assoc::Left, |x, _, y| format!("({x} * {y})"))
+        .infix(op('^'), 4, Assoc::Right, |x, _, y| format!("({x} ^ {y})"))
+        .infix(op('='), 0, Assoc::None, |x, _, y| format!("({x} = {y})"))
+        .postfix(op('!'), 5, |x, _| format!("({x}!)"))
+        .build()
+}
+
+fn parse(text: &str) -> (String, usize) {
+    match expr().parse(text.into()) {
+        ParseResult::Ok(s, pos) => (s, pos.offset),
+        other => panic!("expected Ok, got {other:?}"),
+    }
+}
+
+#[test]
+fn precedence() {
+    assert_eq!(parse("1+2*3").0, "(1 + (2 * 3))");
+    assert_eq!(parse("1*2+3").0, "((1 * 2) + 3)");
+}
+
+#[test]
+fn associativity() {
+    assert_eq!(parse("1-2-3").0, "((1 - 2) - 3)");
+    assert_eq!(parse("1^2^3").0, "(1 ^ (2 ^ 3))");
+}
+
+#[test]
+fn prefix_and_postfix() {
+    assert_eq!(parse("-1*2").0, "((-1) * 2)");
+    assert_eq!(parse("-1^2").0, "(-(1 ^ 2))");
+    assert_eq!(parse("--1").0, "(-(-1))");
+    assert_eq!(parse("-2!").0, "(-(2!))");
+    assert_eq!(parse("2!!").0, "((2!)!)");
+}
+
+#[test]
+fn parentheses() {
+    assert_eq!(parse("(1+2)*3").0, "((1 + 2) * 3)");
+}
+
+#[test]
+fn non_associative_stops() {
+    assert_eq!(parse("1=2=3"), ("(1 = 2)".to_string(), 3));
+}
+
+#[test]
+fn dangling_operator_is_not_consumed() {
+    assert_eq!(parse("1+2+"), ("(1 + 2)".to_string(), 3));
+}
+
+#[test]
+fn no_operand_fails() {
+    assert!(matches!(expr().parse("*1".into()), ParseResult::Fail(..)));
+    assert!(matches!(expr().parse("".into()), ParseResult::Fail(..)));
+}
@@ -0,0 +1,73 @@
+use nessie_parse::{ParseResult, Parser, Session, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+type P<'a, T> = Parser<'a, T, Error>;
+
+fn letter<'a>() -> P<'a, char> {
+    Parser::letter().map_fail(|_| ()).with_name("letter")
+}
+
+fn digit<'a>() -> P<'a, char> {
+    Parser::digit().map_fail(|_| ()).with_name("digit")
+}
+
+/// Letters and digits, where a letter is tried first.
+fn alphanumeric<'a>() -> P<'a, usize> {
+    letter()
+        .or(digit())
+        .map_fail(|((), ())| ())
+        .repeat_0()
+        .map(|chars| chars.len())
+        .with_name("alphanumeric")
+}
+
+#[test]
+fn profile_counts_calls_and_backtracks() {
+    let session = Session::new().with_profiling();
+    let result = alphanumeric().parse(State::new("a1b2c3!").with_session(&session));
+    assert!(matches!(result, ParseResult::Ok(6, _)));
+    let profile = session.take_profile();
+
+    let letter = profile.get("letter").unwrap();
+    // Once for every character, and once more at the `!`.
+    assert_eq!(letter.calls, 7);
+    assert_eq!(letter.backtracks, 4);
+    assert_eq!(letter.bytes, 3);
+    let digit = profile.get("digit").unwrap();
+    assert_eq!((digit.calls, digit.backtracks, digit.bytes), (4, 1, 3));
+    let alphanumeric = profile.get("alphanumeric").unwrap();
+    assert_eq!((alphanumeric.calls, alphanumeric.bytes), (1, 6));
+    assert!(alphanumeric.time >= letter.time);
+
+    assert!(session.take_profile().get("letter").is_none());
+}
+
+#[test]
+fn profile_counts_errors() {
+    let session = Session::new().with_profiling();
+    let parser = letter().or_err::<()>(Error).with_name("required");
+    parser.parse(State::new("1").with_session(&session));
+    let profile = session.take_profile();
+    assert_eq!(profile.get("required").unwrap().errors, 1);
+    assert_eq!(profile.get("letter").unwrap().errors, 0);
+}
+
+#[test]
+fn profile_is_a_table() {
+    let session = Session::new().with_profiling();
+    alphanumeric().parse(State::new("a").with_session(&session));
+    let profile = session.take_profile();
+    let table = profile.to_string();
+    let mut lines = table.lines();
+    let header = lines.next().unwrap();
+    let columns: Vec<_> = header.split_whitespace().collect();
+    assert_eq!(
+        columns,
+        ["rule", "calls", "backtracks", "errors", "bytes", "time"]
+    );
+    // The rule that ran the longest, since it ran all of the others.
+    assert!(lines.next().unwrap().starts_with("alphanumeric "));
+    assert_eq!(lines.count(), profile.rules().len() - 1);
+}
@@ -0,0 +1,164 @@
+use nessie_parse::{ParseResult, Parser, Pos, Session, Span, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Error {
+    ExpectedNumber,
+    ExpectedSemicolon,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Stmt<'a> {
+    Assign(&'a str, &'a str),
+    Error(Span),
+}
+
+fn char_eq<'a>(ch: char) -> Parser<'a, char, Error> {
+    Parser::char_eq(ch)
+}
+
+/// `name = number ;`
+fn assign<'a>() -> Parser<'a, Stmt<'a>, Error> {
+    Parser::letter()
+        .repeat_1()
+        .recognize()
+        .map_fail(|_| ())
+        .and_then(|name| {
+            char_eq('=')
+                .and_then(|_| {
+                    Parser::digit()
+                        .repeat_1()
+                        .recognize()
+                        .or_err(Error::ExpectedNumber)
+                })
+                .and_then(|number| {
+                    char_eq(';')
+                        .or_err(Error::ExpectedSemicolon)
+                        .map(move |_| number)
+                })
+                .map(move |number| Stmt::Assign(name, number))
+        })
+}
+
+fn statement<'a>() -> Parser<'a, Stmt<'a>, Error> {
+    let sync = Parser::skip_until(char_eq(';'))
+        .map_fail(|_| ())
+        .and_then(|()| char_eq(';'));
+    assign().recover_with(sync, Stmt::Error)
+}
+
+fn program<'a>() -> Parser<'a, Vec<Stmt<'a>>, Error> {
+    statement().repeat_0()
+}
+
+#[test]
+fn reports_every_error_and_keeps_going() {
+    let text = "a=1;b=x;c=3;d=;e=5;";
+    let session = Session::new();
+    let state = State::new(text).with_session(&session);
+    let ParseResult::Ok(stmts, pos) = program().parse(state) else {
+        panic!("expected Ok");
+    };
+    assert_eq!(pos.offset, text.len());
+    assert_eq!(stmts.len(), 5);
+    assert_eq!(stmts[0], Stmt::Assign("a", "1"));
+    assert!(matches!(stmts[1], Stmt::Error(span) if state.slice(span) == "b=x;"));
+    assert_eq!(stmts[2], Stmt::Assign("c", "3"));
+    assert!(matches!(stmts[3], Stmt::Error(span) if state.slice(span) == "d=;"));
+    assert_eq!(stmts[4], Stmt::Assign("e", "5"));
+
+    let errors = session.take_errors::<Error>();
+    let offsets: Vec<_> = errors.iter().map(|(e, pos)| (*e, pos.offset)).collect();
+    assert_eq!(
+        offsets,
+        [(Error::ExpectedNumber, 6), (Error::ExpectedNumber, 14)]
+    );
+    assert!(session.errors::<Error>().is_empty());
+}
+
+#[test]
+fn without_a_session_errors_are_fatal() {
+    let result = program().parse("a=1;b=x;c=3;".into());
+    assert!(matches!(result, ParseResult::Err(Error::ExpectedNumber, pos) if pos.offset == 6));
+}
+
+#[test]
+fn unrecoverable_errors_are_returned() {
+    let session = Session::new();
+    let state = State::new("a=1;b=2").with_session(&session);
+    let result = program().parse(state);
+    assert!(matches!(
+        result,
+        ParseResult::Err(Error::ExpectedSemicolon, _)
+    ));
+    assert!(session.errors::<Error>().is_empty());
+}
+
+#[test]
+fn failed_alternatives_leave_no_errors() {
+    let session = Session::new();
+    // This recovers, but then fails on the `!` that follows.
+    let recovered_then_failed = statement().and_then(|_| char_eq('!'));
+    let parser = recovered_then_failed
+        .map(|_| ())
+        .or(statement().map(|_| ()))
+        .map_fail(|((), ())| ());
+    let state = State::new("a=x;").with_session(&session);
+    let result = parser.parse(state);
+    assert!(matches!(result, ParseResult::Ok((), _)));
+    // Only the error from the second alternative is kept.
+    assert_eq!(session.errors::<Error>().len(), 1);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Num(char),
+    Call(Vec<Expr>),
+    Error,
+}
+
+/// `digit | '(' expr,* ')'`, where a bad argument list is replaced by an error.
+fn expr<'a>() -> Parser<'a, Expr, Error> {
+    let call = char_eq('(').and_then(|_| {
+        expr()
+            .sep_by::<_, _, ()>(char_eq(','))
+            .and_then(|args| {
+                // Anything other than `)` here is an error.
+                Parser::not::<()>(char_eq(')'))
+                    .and_then(|()| Parser::err(Error::ExpectedNumber))
+                    .or(Parser::ret(Expr::Call(args)))
+                    .map_fail(|((), ())| ())
+            })
+            .recover_to_delimiter('(', ')', |_| Expr::Error)
+            .and_then(|e| char_eq(')').map(move |_| e.clone()))
+    });
+    Parser::digit()
+        .map(Expr::Num)
+        .map_fail(|_| ())
+        .or(call)
+        .map_fail(|((), ())| ())
+}
+
+#[test]
+fn recovers_to_the_matching_delimiter() {
+    let text = "(1,(2,x(3)),4)";
+    let session = Session::new();
+    let state = State::new(text).with_session(&session);
+    let ParseResult::Ok(e, pos) = expr().parse(state) else {
+        panic!("expected Ok");
+    };
+    assert_eq!(pos.offset, text.len());
+    assert_eq!(
+        e,
+        Expr::Call(vec![Expr::Num('1'), Expr::Error, Expr::Num('4')])
+    );
+    let errors = session.errors::<Error>();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].1,
+        Pos {
+            offset: 5,
+            row: 1,
+            col: 6
+        }
+    );
+}
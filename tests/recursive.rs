@@ -0,0 +1,112 @@
+use nessie_parse::{ForwardDecl, ParseResult, Parser, State};
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+type P<'a, T> = Parser<'a, T, Error>;
+
+fn char_eq<'a>(ch: char) -> P<'a, ()> {
+    P::char_eq(ch).map(|_| ())
+}
+
+fn number<'a>() -> P<'a, i64> {
+    Parser::digit()
+        .repeat_1()
+        .recognize()
+        .map(|digits: &str| digits.parse().unwrap())
+        .map_fail(|_| ())
+}
+
+/// `operand (op operand)*`, folded with `f`.
+fn chain<'a>(operand: P<'a, i64>, op: char, f: fn(i64, i64) -> i64) -> P<'a, i64> {
+    let rest = char_eq(op).and_then({
+        let operand = operand.clone();
+        move |()| operand.clone()
+    });
+    operand.and_then(move |first| {
+        rest.clone()
+            .repeat_0()
+            .map(move |rest: Vec<i64>| rest.into_iter().fold(first, f))
+    })
+}
+
+/// `expr = term ('+' term)*`, `term = atom ('*' atom)*` and
+/// `atom = number | '(' expr ')'`.
+fn calculator<'a>() -> P<'a, i64> {
+    let expr = ForwardDecl::new();
+    let parens = char_eq('(').and_then({
+        let expr = expr.parser();
+        move |()| {
+            expr.clone()
+                .and_then(|value| char_eq(')').map(move |()| value))
+        }
+    });
+    let atom = number().or(parens).map_fail(|((), ())| ());
+    let term = chain(atom, '*', |a, b| a * b);
+    expr.define(chain(term, '+', |a, b| a + b))
+}
+
+#[test]
+fn forward_decl_makes_rules_that_use_each_other() {
+    let calculator = calculator();
+    let result = calculator.parse(State::new("2*(3+4)+1"));
+    assert!(matches!(result, ParseResult::Ok(15, pos) if pos.offset == 9));
+    let result = calculator.parse(State::new("((1))*(2*(3))"));
+    assert!(matches!(result, ParseResult::Ok(6, _)));
+    let result = calculator.parse(State::new("(1+2"));
+    assert!(matches!(result, ParseResult::Fail((), _)));
+}
+
+#[test]
+fn forward_decls_keep_each_other_alive() {
+    let calculator = {
+        let expr = ForwardDecl::new();
+        let term = ForwardDecl::new();
+        let parens = char_eq('(').and_then({
+            let expr = expr.parser();
+            move |()| {
+                expr.clone()
+                    .and_then(|value| char_eq(')').map(move |()| value))
+            }
+        });
+        let atom = number().or(parens).map_fail(|((), ())| ());
+        let expr = expr.define(chain(term.parser(), '+', |a, b| a + b));
+        // Only `expr` is kept, and `term` lives on through it.
+        let _ = term.define(chain(atom, '*', |a, b| a * b));
+        expr
+    };
+    let result = calculator.parse(State::new("1+(2)"));
+    assert!(matches!(result, ParseResult::Ok(3, pos) if pos.offset == 5));
+    let result = calculator.parse(State::new("2*(3+4)+1"));
+    assert!(matches!(result, ParseResult::Ok(15, _)));
+}
+
+#[test]
+fn recursive_builds_the_parser_once() {
+    let built = Rc::new(Cell::new(0));
+    let counter = built.clone();
+    // `nested = '[' nested* ']'`
+    let nested = P::recursive(move |self_ref| {
+        counter.set(counter.get() + 1);
+        let inside = self_ref.repeat_0();
+        char_eq('[')
+            .and_then(move |()| inside.clone())
+            .and_then(|_| char_eq(']'))
+            .map(|()| ())
+    });
+    let result = nested.parse(State::new("[[][[]]]"));
+    assert!(matches!(result, ParseResult::Ok((), pos) if pos.offset == 8));
+    let result = nested.parse(State::new("[[]"));
+    assert!(matches!(result, ParseResult::Fail((), _)));
+    assert_eq!(built.get(), 1);
+}
+
+#[test]
+#[should_panic(expected = "before it was defined")]
+fn forward_decl_must_be_defined() {
+    let decl = ForwardDecl::<(), Error>::new();
+    decl.parser().parse(State::new(""));
+}
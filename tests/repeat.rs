@@ -0,0 +1,180 @@
+use nessie_parse::{Col, ParseResult, Parser, Row, State};
+
+use std::ops::Bound;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+#[test]
+fn long_whitespace_run_does_not_overflow() {
+    let text = (" ".repeat(99) + "\n").repeat(500) + "x";
+    let result = Parser::<(), Error, ()>::skip_whitespace().parse(State::new(&text));
+    let ParseResult::Ok((), pos) = result else {
+        panic!("expected Ok, got {result:?}");
+    };
+    assert_eq!(pos.offset, 50_000);
+}
+
+#[test]
+fn long_line_does_not_overflow() {
+    let text = " ".repeat(70_000) + "x";
+    let result = Parser::<_, Error, _>::whitespace()
+        .repeat_0::<()>()
+        .parse(State::new(&text));
+    let ParseResult::Ok(spaces, pos) = result else {
+        panic!("expected Ok, got {result:?}");
+    };
+    assert_eq!(spaces.len(), 70_000);
+    assert_eq!(pos.offset, 70_000);
+    assert_eq!((pos.row, pos.col), (1, Col::MAX));
+
+    let text = "\n".repeat(70_000);
+    let result = Parser::<(), Error, ()>::skip_whitespace().parse(State::new(&text));
+    assert!(matches!(result, ParseResult::Ok((), pos) if pos.row == Row::MAX));
+}
+
+#[test]
+fn repeat_0_collects_in_order() {
+    let result = Parser::<_, Error, _>::digit()
+        .repeat_0::<()>()
+        .parse("123a".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v == ['1', '2', '3'] && pos.offset == 3));
+}
+
+#[test]
+fn repeat_0_on_no_match_is_empty() {
+    let result = Parser::<_, Error, _>::digit()
+        .repeat_0::<()>()
+        .parse("abc".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v.is_empty() && pos.offset == 0));
+}
+
+#[test]
+fn repeat_1_fails_on_no_match() {
+    let result = Parser::<_, Error, _>::digit()
+        .repeat_1()
+        .parse("abc".into());
+    assert!(matches!(result, ParseResult::Fail(..)));
+}
+
+#[test]
+fn repeat_propagates_errors() {
+    let digit_or_err = Parser::<_, Error, _>::digit().and_then(|d| {
+        if d == '9' {
+            Parser::err(Error)
+        } else {
+            Parser::ret(d)
+        }
+    });
+    let result = digit_or_err.clone().repeat_0::<()>().parse("129".into());
+    assert!(matches!(result, ParseResult::Err(Error, pos) if pos.offset == 3));
+    let result = digit_or_err.repeat_1().parse("9".into());
+    assert!(matches!(result, ParseResult::Err(Error, _)));
+}
+
+#[test]
+fn repeat_of_non_consuming_parser_terminates() {
+    let nothing = Parser::<(), Error, ()>::ret(());
+    // An item that consumed nothing is kept, and is the last one.
+    let result = nothing.clone().repeat_0::<()>().parse("abc".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v.len() == 1 && pos.offset == 0));
+    let result = nothing.repeat_1().parse("abc".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v.len() == 1 && pos.offset == 0));
+    let maybe_digit = Parser::<_, Error, _>::digit().maybe::<()>();
+    let result = maybe_digit.repeat_0::<()>().parse("12a".into());
+    assert!(
+        matches!(result, ParseResult::Ok(v, pos) if v == [Some('1'), Some('2'), None] && pos.offset == 2)
+    );
+}
+
+#[test]
+fn repeat_exact_takes_exactly_n() {
+    let result = Parser::<_, Error, _>::digit()
+        .repeat_exact(2)
+        .parse("123".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v == ['1', '2'] && pos.offset == 2));
+}
+
+#[test]
+fn repeat_exact_reports_too_few() {
+    let result = Parser::<_, Error, _>::digit()
+        .repeat_exact(3)
+        .parse("12a".into());
+    let ParseResult::Fail(too_few, _) = result else {
+        panic!("expected Fail, got {result:?}");
+    };
+    assert_eq!(too_few.matched, 2);
+    assert_eq!(too_few.min, 3);
+    assert_eq!(too_few.pos.offset, 2);
+}
+
+#[test]
+fn repeat_range_stops_at_max() {
+    let digits = || Parser::<_, Error, _>::digit();
+    let result = digits().repeat_range(1..=3).parse("12345".into());
+    assert!(matches!(result, ParseResult::Ok(v, _) if v == ['1', '2', '3']));
+    let result = digits().repeat_range(1..3).parse("12345".into());
+    assert!(matches!(result, ParseResult::Ok(v, _) if v == ['1', '2']));
+    let result = digits().repeat_range(2..).parse("12345".into());
+    assert!(matches!(result, ParseResult::Ok(v, _) if v.len() == 5));
+    let result = digits().repeat_range(2..).parse("1".into());
+    assert!(matches!(result, ParseResult::Fail(f, _) if f.matched == 1 && f.min == 2));
+}
+
+#[test]
+#[should_panic(expected = "repeat_range: empty range")]
+fn repeat_range_rejects_an_empty_range() {
+    Parser::<_, Error, _>::digit().repeat_range(0..0);
+}
+
+#[test]
+#[should_panic(expected = "repeat_range: empty range")]
+fn repeat_range_rejects_a_start_past_the_end() {
+    let range = (Bound::Excluded(usize::MAX), Bound::Unbounded);
+    Parser::<_, Error, _>::digit().repeat_range(range);
+}
+
+#[test]
+fn repeat_until_returns_terminator() {
+    let result = Parser::<_, Error, _>::char()
+        .repeat_until::<_, _, ()>(Parser::char_eq(';').map_fail(|()| ()))
+        .parse("ab;c".into());
+    let ParseResult::Ok((items, terminator), pos) = result else {
+        panic!("expected Ok, got {result:?}");
+    };
+    assert_eq!(items, ['a', 'b']);
+    assert_eq!(terminator, ';');
+    assert_eq!(pos.offset, 3);
+}
+
+#[test]
+fn repeat_until_fails_without_terminator() {
+    let result = Parser::<_, Error, _>::digit()
+        .repeat_until::<_, (), ()>(Parser::char_eq(';'))
+        .parse("12a;".into());
+    assert!(matches!(result, ParseResult::Fail((), pos) if pos.offset == 2));
+}
+
+#[test]
+fn repetitions_stop_after_an_item_that_consumed_nothing() {
+    let nothing = || Parser::<(), Error, ()>::ret(());
+    let result = nothing().repeat_range(2..).parse("abc".into());
+    let ParseResult::Fail(too_few, _) = result else {
+        panic!("expected Fail, got {result:?}");
+    };
+    assert_eq!((too_few.matched, too_few.min, too_few.fail), (1, 2, None));
+    let result = nothing().repeat_range(0..=3).parse("abc".into());
+    assert!(matches!(result, ParseResult::Ok(v, _) if v.len() == 1));
+
+    let sign = || Parser::<_, Error, ()>::char_eq('-').maybe::<()>();
+    let terminator = || Parser::<_, Error, ()>::char_eq(';');
+    let result = sign()
+        .repeat_until::<_, _, ()>(terminator())
+        .parse("--;".into());
+    assert!(matches!(result, ParseResult::Ok((v, ';'), _) if v.len() == 2));
+    let result = sign()
+        .repeat_until::<_, _, ((), Option<()>)>(terminator())
+        .parse("-x".into());
+    // The second item consumed nothing, and the terminator did not match.
+    assert!(matches!(result, ParseResult::Fail(((), None), pos) if pos.offset == 1));
+}
@@ -0,0 +1,91 @@
+use nessie_parse::{ParseResult, Parser, Trailing, TrailingSeparator};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Error {
+    Trailing(TrailingSeparator),
+}
+
+impl From<TrailingSeparator> for Error {
+    fn from(t: TrailingSeparator) -> Self {
+        Error::Trailing(t)
+    }
+}
+
+fn digit<'a>() -> Parser<'a, char, Error> {
+    Parser::digit().map_fail(|_| ())
+}
+
+fn comma<'a>() -> Parser<'a, char, Error> {
+    Parser::char_eq(',')
+}
+
+#[test]
+fn sep_by_parses_a_list() {
+    let result = digit().sep_by::<_, _, ()>(comma()).parse("1,2,3)".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v == ['1', '2', '3'] && pos.offset == 5));
+}
+
+#[test]
+fn sep_by_empty() {
+    let result = digit().sep_by::<_, _, ()>(comma()).parse(")".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v.is_empty() && pos.offset == 0));
+}
+
+#[test]
+fn sep_by_leaves_trailing_separator() {
+    let result = digit().sep_by::<_, _, ()>(comma()).parse("1,2,)".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v == ['1', '2'] && pos.offset == 3));
+}
+
+#[test]
+fn sep_by_1_needs_an_item() {
+    let result = digit().sep_by_1(comma()).parse(")".into());
+    assert!(matches!(result, ParseResult::Fail((), _)));
+    let result = digit().sep_by_1(comma()).parse("1)".into());
+    assert!(matches!(result, ParseResult::Ok(v, _) if v == ['1']));
+}
+
+#[test]
+fn trailing_allow() {
+    let list = || digit().sep_end_by::<_, _, ()>(comma(), Trailing::Allow);
+    let result = list().parse("1,2,)".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v == ['1', '2'] && pos.offset == 4));
+    let result = list().parse("1,2)".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v == ['1', '2'] && pos.offset == 3));
+}
+
+#[test]
+fn trailing_forbid() {
+    let list = || digit().sep_end_by::<_, _, ()>(comma(), Trailing::Forbid);
+    let result = list().parse("1,2,)".into());
+    assert!(matches!(
+        result,
+        ParseResult::Err(Error::Trailing(TrailingSeparator::Forbidden), pos) if pos.offset == 3
+    ));
+    let result = list().parse("1,2)".into());
+    assert!(matches!(result, ParseResult::Ok(v, _) if v == ['1', '2']));
+}
+
+#[test]
+fn trailing_require() {
+    let list = || digit().sep_end_by::<_, _, ()>(comma(), Trailing::Require);
+    let result = list().parse("1,2)".into());
+    assert!(matches!(
+        result,
+        ParseResult::Err(Error::Trailing(TrailingSeparator::Missing), pos) if pos.offset == 3
+    ));
+    let result = list().parse("1,2,)".into());
+    assert!(matches!(result, ParseResult::Ok(v, pos) if v == ['1', '2'] && pos.offset == 4));
+    let result = list().parse(")".into());
+    assert!(matches!(result, ParseResult::Ok(v, _) if v.is_empty()));
+}
+
+#[test]
+fn sep_by_stops_after_an_item_that_consumed_nothing() {
+    let item = digit().maybe::<()>();
+    let sep = comma().maybe::<()>();
+    let result = item.sep_by::<_, _, ()>(sep).parse("1,2x".into());
+    assert!(
+        matches!(result, ParseResult::Ok(v, pos) if v == [Some('1'), Some('2'), None] && pos.offset == 3)
+    );
+}
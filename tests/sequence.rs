@@ -0,0 +1,124 @@
+use nessie_parse::{Expected, Grammar, ParseResult, Parser, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+type P<'a, T> = Parser<'a, T, Error>;
+
+fn char_eq<'a>(ch: char) -> P<'a, char> {
+    Parser::char_eq(ch)
+}
+
+fn digit<'a>() -> P<'a, char> {
+    Parser::digit().map_fail(|_| ())
+}
+
+#[test]
+fn then_skip_and_preceded_by() {
+    let pair: P<(char, char)> = digit().then(char_eq('x'));
+    assert!(matches!(
+        pair.parse(State::new("1x")),
+        ParseResult::Ok(('1', 'x'), _)
+    ));
+    assert!(matches!(pair.parse(State::new("1y")), ParseResult::Fail((), pos) if pos.offset == 2));
+
+    let first: P<char> = digit().skip(char_eq(';'));
+    let result = first.parse(State::new("1;"));
+    assert!(matches!(result, ParseResult::Ok('1', pos) if pos.offset == 2));
+
+    let second: P<char> = digit().preceded_by(char_eq('-'));
+    let result = second.parse(State::new("-1"));
+    assert!(matches!(result, ParseResult::Ok('1', pos) if pos.offset == 2));
+    assert!(matches!(
+        second.parse(State::new("1")),
+        ParseResult::Fail(..)
+    ));
+}
+
+#[test]
+fn delimited_and_seq() {
+    let group: P<char> = Parser::delimited(char_eq('('), digit(), char_eq(')').or_err::<()>(Error));
+    assert!(matches!(
+        group.parse(State::new("(1)")),
+        ParseResult::Ok('1', _)
+    ));
+    assert!(
+        matches!(group.parse(State::new("(1")), ParseResult::Err(Error, pos) if pos.offset == 2)
+    );
+
+    let date: P<(char, char, char, char, char)> =
+        Parser::seq((digit(), char_eq('-'), digit(), char_eq('-'), digit()));
+    let result = date.parse(State::new("1-2-3"));
+    assert!(matches!(result, ParseResult::Ok(('1', '-', '2', '-', '3'), pos) if pos.offset == 5));
+    let result = date.parse(State::new("1-2+3"));
+    assert!(matches!(result, ParseResult::Fail((), pos) if pos.offset == 4));
+}
+
+#[test]
+fn seq_keeps_the_failure() {
+    let digit = || Parser::digit().label("digit");
+    let dash = || char_eq('-').label("dash");
+    let range: Parser<_, Error, Expected> = Parser::seq((digit(), dash(), digit()));
+    match range.parse(State::new("1-x")) {
+        ParseResult::Fail(expected, _) => {
+            assert_eq!(expected.labels, ["digit"]);
+            assert_eq!(expected.pos.offset, 2);
+            assert_eq!(expected.found, Some('x'));
+        }
+        result => panic!("expected a failure, got {result:?}"),
+    }
+
+    let pair: Parser<_, Error, Expected> = digit().then(dash());
+    match pair.parse(State::new("1+")) {
+        ParseResult::Fail(expected, _) => {
+            assert_eq!(expected.labels, ["dash"]);
+            assert_eq!(expected.found, Some('+'));
+        }
+        result => panic!("expected a failure, got {result:?}"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fail {
+    NotADigit,
+    NotADash,
+}
+
+#[test]
+fn then_combines_the_failure_that_happened() {
+    let d = || digit().map_fail(|()| Fail::NotADigit);
+    let pair: Parser<_, Error, (Option<Fail>, Option<Fail>)> = d().then(d());
+    let result = pair.parse(State::new("x1"));
+    assert!(matches!(
+        result,
+        ParseResult::Fail((Some(Fail::NotADigit), None), _)
+    ));
+    let result = pair.parse(State::new("1x"));
+    assert!(
+        matches!(result, ParseResult::Fail((None, Some(Fail::NotADigit)), pos) if pos.offset == 2)
+    );
+
+    let dash = char_eq('-').map_fail(|()| Fail::NotADash);
+    let range: Parser<_, Error, Vec<Fail>> = Parser::seq((d(), dash, d()));
+    let result = range.parse(State::new("1+2"));
+    assert!(
+        matches!(result, ParseResult::Fail(ref fails, pos) if fails == &[Fail::NotADash] && pos.offset == 2)
+    );
+}
+
+#[test]
+fn seq_is_described_as_a_sequence() {
+    let number = digit().then(digit()).map_fail(|()| ());
+    let signed: P<_> = number
+        .preceded_by::<_, _, ()>(char_eq('-'))
+        .skip(char_eq(';'));
+    assert_eq!(
+        signed.describe(),
+        Grammar::Seq(vec![
+            Grammar::Literal("-".to_string()),
+            Grammar::Terminal("digit".to_string()),
+            Grammar::Terminal("digit".to_string()),
+            Grammar::Literal(";".to_string()),
+        ])
+    );
+}
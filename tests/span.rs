@@ -0,0 +1,80 @@
+use nessie_parse::{ParseResult, Parser, Pos, Span, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+fn word<'a>() -> Parser<'a, Vec<char>, Error> {
+    Parser::letter().repeat_1().map_fail(|_| ())
+}
+
+#[test]
+fn spanned_covers_the_parsed_text() {
+    let text = "  hello world";
+    let state = State::new(text);
+    let parser = Parser::skip_whitespace().and_then(|()| word().spanned());
+    let ParseResult::Ok((_, span), _) = parser.parse(state) else {
+        panic!("expected Ok");
+    };
+    assert_eq!(span.start.offset, 2);
+    assert_eq!(span.end.offset, 7);
+    assert_eq!(span.len(), 5);
+    assert_eq!(state.slice(span), "hello");
+}
+
+#[test]
+fn spans_across_lines() {
+    let text = "ab\ncd";
+    let state = State::new(text);
+    let parser = Parser::<_, Error, _>::char().repeat_0::<()>().spanned();
+    let ParseResult::Ok((_, span), _) = parser.parse(state) else {
+        panic!("expected Ok");
+    };
+    assert_eq!(span.to_string(), "1:1-2:3");
+    assert_eq!(state.slice(span), text);
+}
+
+#[test]
+fn with_span_maps() {
+    let text = "שלום!";
+    let state = State::new(text);
+    let parser = Parser::<_, Error, _>::char()
+        .filter(|c| c.is_alphabetic())
+        .repeat_1()
+        .map_fail(|_| ())
+        .with_span(|_, span| span);
+    let ParseResult::Ok(span, _) = parser.parse(state) else {
+        panic!("expected Ok");
+    };
+    assert_eq!(state.slice(span), "שלום");
+    assert_eq!(span.end.col, 5);
+}
+
+#[test]
+fn joining_spans() {
+    let a = Span::new(Pos::start(), Pos::start().advance('a'));
+    let b = Span::at(Pos::start().advance('a').advance('b'));
+    let joined = a.to(b);
+    assert_eq!(joined.start, a.start);
+    assert_eq!(joined.end, b.end);
+    assert!(joined.contains(Pos::start().advance('a')));
+    assert!(!joined.contains(b.end));
+}
+
+#[test]
+fn recognize_borrows_the_source() {
+    let text = "hello world";
+    let result = word().recognize().parse(text.into());
+    let ParseResult::Ok(s, _) = result else {
+        panic!("expected Ok");
+    };
+    assert_eq!(s, "hello");
+    assert_eq!(s.as_ptr(), text.as_ptr());
+}
+
+#[test]
+fn with_consumed_keeps_the_value() {
+    let result = word().with_consumed().parse("hi!".into());
+    assert!(matches!(result, ParseResult::Ok((v, "hi"), _) if v == ['h', 'i']));
+    let result = word().recognize().parse("!".into());
+    assert!(matches!(result, ParseResult::Fail((), _)));
+}
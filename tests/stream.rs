@@ -0,0 +1,120 @@
+use nessie_parse::{BinaryFailure, Driver, ParseResult, Parser, State};
+
+use std::io::{self, Read};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+#[test]
+fn partial_input_asks_for_more() {
+    let ping = || Parser::<char, Error, _>::expect_string("PING");
+    let result = ping().parse(State::new("PI").with_partial(true));
+    assert!(matches!(result, ParseResult::Incomplete(2)));
+    let result = ping().parse(State::new("PI"));
+    assert!(matches!(result, ParseResult::Fail(..)));
+    let result = ping().parse(State::new("PO").with_partial(true));
+    assert!(matches!(result, ParseResult::Fail(..)));
+
+    let number = Parser::<u32, Error, BinaryFailure, [u8]>::u32_le();
+    let result = number.parse(State::of([1, 2].as_slice()).with_partial(true));
+    assert!(matches!(result, ParseResult::Incomplete(2)));
+}
+
+#[test]
+fn combinators_pass_incomplete_on() {
+    let word = Parser::<_, Error, _>::letter().repeat_1().recognize();
+    let result = word.parse(State::new("abc").with_partial(true));
+    assert!(matches!(result, ParseResult::Incomplete(1)));
+
+    // `or` can't pick the second alternative before it knows the first one
+    // fails.
+    let either = Parser::<char, Error, _>::expect_string("abc")
+        .map_fail(|_| ())
+        .or(Parser::expect_string("a").map_fail(|_| ()))
+        .map_fail(|((), ())| ());
+    let result = either.parse(State::new("ab").with_partial(true));
+    assert!(matches!(result, ParseResult::Incomplete(1)));
+    let result = either.parse(State::new("ab"));
+    assert!(matches!(result, ParseResult::Ok((), pos) if pos.offset == 1));
+}
+
+/// A reader that gives out a few bytes at a time, like a slow socket.
+struct Trickle<'a> {
+    bytes: &'a [u8],
+    step: usize,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.step.min(buf.len()).min(self.bytes.len());
+        buf[..n].copy_from_slice(&self.bytes[..n]);
+        self.bytes = &self.bytes[n..];
+        Ok(n)
+    }
+}
+
+/// A line of text, without the newline.
+fn line<'a>() -> Parser<'a, String, Error> {
+    Parser::char()
+        .filter(|&c| c != '\n')
+        .repeat_0()
+        .recognize()
+        .and_then(|text| Parser::char_eq('\n').map(move |_| text.to_string()))
+}
+
+#[test]
+fn driver_reads_messages_as_needed() {
+    let text = "PING\nSAY héllo\n\nQUIT\n";
+    let mut driver = Driver::new(Trickle {
+        bytes: text.as_bytes(),
+        step: 3,
+    });
+    let mut lines = vec![];
+    while let Some(result) = driver.next(|state| line().parse(state)).unwrap() {
+        let ParseResult::Ok(line, _) = result else {
+            panic!("expected Ok, got {result:?}");
+        };
+        lines.push(line);
+    }
+    assert_eq!(lines, ["PING", "SAY héllo", "", "QUIT"]);
+}
+
+#[test]
+fn driver_stops_at_what_it_cannot_parse() {
+    let mut driver = Driver::new("one\ntwo".as_bytes()).with_chunk_size(2);
+    let first = driver.next(|state| line().parse(state)).unwrap();
+    assert!(matches!(first, Some(ParseResult::Ok(ref line, _)) if line == "one"));
+    // The reader ended without a newline.
+    let second = driver.next(|state| line().parse(state)).unwrap();
+    assert!(matches!(second, Some(ParseResult::Fail(..))));
+    assert_eq!(driver.buffer(), b"two");
+
+    let mut driver = Driver::new([0x80, b'\n'].as_slice());
+    let error = driver.next(|state| line().parse(state)).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+}
+
+fn u16_le<'a>() -> Parser<'a, u16, Error, BinaryFailure, [u8]> {
+    Parser::u16_le()
+}
+
+#[test]
+fn driver_parses_bytes() {
+    let bytes = [1, 0, 2, 0, 3];
+    let mut driver = Driver::new(Trickle {
+        bytes: &bytes,
+        step: 1,
+    });
+    assert!(matches!(
+        driver.next_bytes(|state| u16_le().parse(state)).unwrap(),
+        Some(ParseResult::Ok(1, _))
+    ));
+    assert!(matches!(
+        driver.next_bytes(|state| u16_le().parse(state)).unwrap(),
+        Some(ParseResult::Ok(2, _))
+    ));
+    assert!(matches!(
+        driver.next_bytes(|state| u16_le().parse(state)).unwrap(),
+        Some(ParseResult::Fail(BinaryFailure::Eof { needed: 1 }, _))
+    ));
+}
@@ -1,4 +1,4 @@
-use nessie_parse::{one_of, ParseResult, Parser};
+use nessie_parse::{one_of, parse, ParseResult, Parser};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Error {
@@ -6,17 +6,13 @@ enum Error {
     IdentifierIsReservedWord(&'static str),
 }
 
-fn number<'a>() -> Parser<'a, String, Error> {
-    Parser::digit()
-        .repeat_1()
-        .map(|digits| digits.into_iter().collect())
-        .map_fail(|_| ())
-        .and_then(|digits: String| {
-            Parser::not::<()>(Parser::letter())
-                .or_err(Error::CantHaveLetterAfterNumber)
-                .map(move |_| digits.clone())
-        })
-        .with_name("number")
+fn number<'a>() -> Parser<'a, &'a str, Error> {
+    parse! {
+        let digits = Parser::digit().repeat_1().recognize().map_fail(|_| ());
+        Parser::not::<()>(Parser::letter()).or_err(Error::CantHaveLetterAfterNumber);
+        ret digits
+    }
+    .with_name("number")
 }
 
 #[test]
@@ -0,0 +1,86 @@
+use nessie_parse::{Outcome, ParseResult, Parser, Session, State};
+
+use indoc::indoc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error;
+
+type P<'a, T> = Parser<'a, T, Error>;
+
+/// A single character. This is written by hand so it is a leaf in the trace
+/// (`Parser::char_eq` is made of other parsers, which would show up too).
+fn char_eq<'a>(ch: char) -> P<'a, char> {
+    P::from_fn(move |state| match state.next_char() {
+        Some((c, pos)) if c == ch => ParseResult::Ok(c, pos),
+        Some((_, pos)) => ParseResult::Fail((), pos),
+        None => ParseResult::Fail((), state.pos),
+    })
+    .with_name(format!("'{ch}'"))
+}
+
+/// `ab` or `ac`.
+fn ab_or_ac<'a>() -> P<'a, char> {
+    let ab = char_eq('a').and_then(|_| char_eq('b')).with_name("ab");
+    let ac = char_eq('a').and_then(|_| char_eq('c')).with_name("ac");
+    ab.or(ac).map_fail(|((), ())| ()).with_name("ab_or_ac")
+}
+
+#[test]
+fn tracing_shows_every_parser() {
+    let session = Session::new().with_tracing();
+    let result = ab_or_ac().parse(State::new("ac").with_session(&session));
+    assert!(matches!(result, ParseResult::Ok('c', _)));
+    let trace = session.take_trace();
+    assert_eq!(
+        trace.to_string(),
+        indoc! {"
+            ab_or_ac 1:1 -> Ok 1:3
+              or(ab | ac) 1:1 -> Ok 1:3
+                ab 1:1 -> Fail 1:3
+                  'a' 1:1 -> Ok 1:2
+                  'b' 1:2 -> Fail 1:3
+                ac 1:1 -> Ok 1:3
+                  'a' 1:1 -> Ok 1:2
+                  'c' 1:2 -> Ok 1:3
+        "}
+    );
+    assert_eq!(
+        trace.roots[0].children[0].children[0].outcome,
+        Outcome::Fail
+    );
+    assert!(session.take_trace().is_empty());
+}
+
+#[test]
+fn traced_only_traces_its_parser() {
+    let session = Session::new();
+    let parser = char_eq('a').and_then(|_| char_eq('b').traced());
+    parser.parse(State::new("ab").with_session(&session));
+    assert_eq!(session.take_trace().to_string(), "'b' 1:2 -> Ok 1:3\n");
+
+    parser.parse(State::new("ax").with_session(&session));
+    assert_eq!(session.take_trace().to_string(), "'b' 1:2 -> Fail 1:3\n");
+}
+
+#[test]
+fn traced_without_a_session_only_parses() {
+    let parser = char_eq('a').traced();
+    let result = parser.parse(State::new("a"));
+    assert!(matches!(result, ParseResult::Ok(_, pos) if pos.offset == 1));
+}
+
+#[test]
+fn traces_as_json() {
+    let session = Session::new();
+    let parser = char_eq('"').traced();
+    parser.parse(State::new("\"").with_session(&session));
+    let pos = |offset, col| format!(r#"{{"offset":{offset},"row":1,"col":{col}}}"#);
+    assert_eq!(
+        session.take_trace().to_json(),
+        format!(
+            r#"[{{"name":"'\"'","start":{},"outcome":"Ok","end":{},"children":[]}}]"#,
+            pos(0, 1),
+            pos(1, 2),
+        )
+    );
+}
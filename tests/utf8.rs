@@ -0,0 +1,84 @@
+use nessie_parse::{ColumnMode, ParseResult, Parser, Pos, State};
+
+fn chars<'a>() -> Parser<'a, Vec<char>, ()> {
+    Parser::char().repeat_0()
+}
+
+fn end_pos(state: State) -> Pos {
+    match chars().parse(state) {
+        ParseResult::Ok(_, pos) => pos,
+        other => panic!("expected Ok, got {other:?}"),
+    }
+}
+
+#[test]
+fn hebrew_offsets_are_byte_offsets() {
+    let text = "שלום";
+    let pos = end_pos(text.into());
+    assert_eq!(pos.offset, text.len());
+    assert_eq!(pos.col, 5);
+    assert_eq!(State::new(text).with_pos(pos).rest(), "");
+}
+
+#[test]
+fn rest_after_non_ascii_does_not_panic() {
+    let text = "日本語 rest";
+    let result = Parser::<_, (), _>::char()
+        .repeat_0::<()>()
+        .map(|_| ())
+        .parse(text.into());
+    assert!(matches!(result, ParseResult::Ok((), _)));
+
+    let state = State::new(text);
+    let mut pos = state.pos;
+    for _ in 0..3 {
+        let ParseResult::Ok(_, next) = Parser::<_, (), _>::char().parse(state.with_pos(pos)) else {
+            panic!("expected a character");
+        };
+        pos = next;
+    }
+    assert_eq!(state.with_pos(pos).rest(), " rest");
+    assert_eq!(pos.col, 4);
+}
+
+#[test]
+fn emoji_and_newlines() {
+    let text = "🦕\n🦖x";
+    let pos = end_pos(text.into());
+    assert_eq!(pos.offset, text.len());
+    assert_eq!(pos.row, 2);
+    assert_eq!(pos.col, 3);
+}
+
+#[test]
+fn expect_string_with_non_ascii() {
+    let text = "שלום\nעולם!";
+    let result = Parser::<char, (), _>::expect_string("שלום\nעולם").parse(text.into());
+    let ParseResult::Ok((), pos) = result else {
+        panic!("expected Ok, got {result:?}");
+    };
+    assert_eq!(State::new(text).with_pos(pos).rest(), "!");
+    assert_eq!(pos.row, 2);
+    assert_eq!(pos.col, 5);
+}
+
+#[test]
+fn grapheme_columns() {
+    // A family emoji made of several code points joined together, and an `e`
+    // followed by a combining acute accent.
+    let text = "👨‍👩‍👧e\u{301}x";
+    let chars_pos = end_pos(State::new(text));
+    let graphemes_pos = end_pos(State::new(text).with_column_mode(ColumnMode::Graphemes));
+    assert_eq!(chars_pos.offset, text.len());
+    assert_eq!(graphemes_pos.offset, text.len());
+    assert_eq!(chars_pos.col, 1 + text.chars().count() as u16);
+    assert_eq!(graphemes_pos.col, 1 + 3);
+}
+
+#[test]
+fn crlf_in_grapheme_mode_is_one_newline() {
+    let text = "a\r\nb";
+    let pos = end_pos(State::new(text).with_column_mode(ColumnMode::Graphemes));
+    assert_eq!(pos.row, 2);
+    assert_eq!(pos.col, 2);
+}